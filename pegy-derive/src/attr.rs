@@ -0,0 +1,99 @@
+use proc_macro2::TokenStream;
+
+/// options given by `#[parse(...)]` on a derived type.
+#[derive(Default)]
+pub struct RuleOptions {
+    /// label that replaces the expected set when the rule fails at its start
+    pub expected: Option<syn::LitStr>,
+    /// error code attached to errors of this rule
+    pub code: Option<syn::LitStr>,
+    /// help text attached to errors of this rule
+    pub help: Option<syn::LitStr>,
+    /// warning emitted when the rule succeeds
+    pub warn: Option<syn::LitStr>,
+    /// marks the enum variant produced when no other variant matches
+    pub error: bool,
+    /// the tokens of the sources the rule parses, if its grammar matches none itself
+    pub token: Option<syn::Type>,
+}
+
+impl RuleOptions {
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+
+        for attr in attrs {
+            if !attr.path().is_ident("parse") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("expected") {
+                    options.expected = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("code") {
+                    options.code = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("help") {
+                    options.help = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("error") {
+                    options.error = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("warn") {
+                    options.warn = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("token") {
+                    options.token = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                return Err(meta.error("unknown parse option"));
+            })?;
+        }
+
+        return Ok(options);
+    }
+
+    /// generates an expression that maps the error `e` of a rule starting at `_start`.
+    pub fn map_error(&self) -> TokenStream {
+        let mut stream = quote::quote!(e);
+
+        if let Some(expected) = &self.expected {
+            stream = quote::quote! {
+                if e.span.0 == _start{
+                    e.label(#expected)
+                } else{
+                    e
+                }
+            };
+        }
+
+        if let Some(code) = &self.code {
+            stream = quote::quote!((#stream).with_code(#code));
+        }
+
+        if let Some(help) = &self.help {
+            stream = quote::quote!((#stream).with_help(#help));
+        }
+
+        return stream;
+    }
+
+    /// generates a statement that emits the warning, if any,
+    /// for a rule that matched from `_start` to the current position.
+    pub fn emit_warning(&self) -> TokenStream {
+        match &self.warn {
+            Some(warn) => quote::quote! {
+                src.warn(::pegy::Error::warning(
+                    ::pegy::Span::new(_start, src.current_position()),
+                    #warn
+                ));
+            },
+            None => TokenStream::new(),
+        }
+    }
+}
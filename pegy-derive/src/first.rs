@@ -0,0 +1,207 @@
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::{ToTokens, TokenStreamExt};
+
+/// The characters a grammar can start with.
+enum First {
+    /// a condition on the character `_c`
+    Chars(TokenStream),
+    /// the term may match without consuming input, or is not known
+    Any,
+    /// the term never consumes input, e.g. a lookahead
+    ZeroWidth,
+}
+
+/// generates the body of `Parse::may_start_with` for a grammar over
+/// the tokens `token`, an expression on the character `_c`.
+///
+/// Grammar errors are reported by the grammar parser, here they make the rule
+/// match any character.
+pub fn may_start_with(grammar: TokenStream, token: &TokenStream) -> TokenStream {
+    let tokens = grammar.into_iter().collect::<Vec<TokenTree>>();
+
+    match alternatives(&tokens, token) {
+        First::Chars(c) => c,
+        First::Any | First::ZeroWidth => quote::quote!(true),
+    }
+}
+
+fn alternatives(tokens: &[TokenTree], token: &TokenStream) -> First {
+    let mut conditions = Vec::new();
+
+    for terms in tokens.split(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == '|')) {
+        match sequence(terms, token) {
+            First::Chars(c) => conditions.push(c),
+            First::Any | First::ZeroWidth => return First::Any,
+        }
+    }
+
+    if conditions.len() == 1 {
+        return First::Chars(conditions.pop().unwrap());
+    }
+    return First::Chars(quote::quote!(#((#conditions))||*));
+}
+
+fn sequence(tokens: &[TokenTree], token: &TokenStream) -> First {
+    let mut pos = 0;
+
+    while pos < tokens.len() {
+        match term(tokens, &mut pos, token) {
+            // the next term starts at the same position
+            First::ZeroWidth => continue,
+            first => return first,
+        }
+    }
+
+    return First::Any;
+}
+
+fn term(tokens: &[TokenTree], pos: &mut usize, token: &TokenStream) -> First {
+    let t = &tokens[*pos];
+    *pos += 1;
+
+    let first = match t {
+        TokenTree::Punct(p) if p.as_char() == '$' => {
+            // `$ident:term`
+            *pos += 2;
+            if *pos >= tokens.len() {
+                return First::Any;
+            }
+            return term(tokens, pos, token);
+        }
+        TokenTree::Punct(p) if p.as_char() == '!' => {
+            if *pos >= tokens.len() {
+                return First::Any;
+            }
+            term(tokens, pos, token);
+            First::ZeroWidth
+        }
+        TokenTree::Ident(i) if i == "_" => {
+            if *pos >= tokens.len() {
+                return First::Any;
+            }
+            return term(tokens, pos, token);
+        }
+        TokenTree::Ident(i) if i == "recover" => {
+            *pos += 1;
+            First::Any
+        }
+        TokenTree::Ident(i)
+            if i == "token"
+                && matches!(tokens.get(*pos), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) =>
+        {
+            // a token pattern, e.g. `token(Token::Ident(_))`
+            *pos += 1;
+            First::Any
+        }
+        TokenTree::Ident(i) => {
+            let mut ty = i.to_token_stream();
+
+            // a path, e.g. `pegy::util::ANY`
+            while matches!(tokens.get(*pos), Some(TokenTree::Punct(p)) if p.as_char() == ':') {
+                ty.extend(tokens.iter().skip(*pos).take(3).cloned());
+                *pos += 3;
+            }
+
+            if let Some(TokenTree::Punct(p)) = tokens.get(*pos) {
+                if p.as_char() == '<' {
+                    let mut depth = 0;
+                    while let Some(t) = tokens.get(*pos) {
+                        *pos += 1;
+                        ty.append(t.clone());
+                        match t {
+                            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+                            TokenTree::Punct(p) if p.as_char() == '>' => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            First::Chars(quote::quote!(<#ty as ::pegy::Parse<#token>>::may_start_with(_c)))
+        }
+        TokenTree::Literal(lit) => match syn::parse2::<syn::Lit>(lit.to_token_stream()) {
+            Ok(syn::Lit::Str(s)) => match s.value().chars().next() {
+                Some(c) => First::Chars(quote::quote!(_c == #c)),
+                None => First::Any,
+            },
+            Ok(syn::Lit::Char(c)) => First::Chars(quote::quote!(_c == #c)),
+            _ => First::Any,
+        },
+        TokenTree::Group(g) => match g.delimiter() {
+            Delimiter::Bracket => class(g.stream()),
+            Delimiter::Parenthesis | Delimiter::None => {
+                alternatives(&g.stream().into_iter().collect::<Vec<_>>(), token)
+            }
+            Delimiter::Brace => First::Any,
+        },
+        _ => First::Any,
+    };
+
+    return quantifier(tokens, pos, first);
+}
+
+/// applies the quantifier and label behind a term
+fn quantifier(tokens: &[TokenTree], pos: &mut usize, first: First) -> First {
+    let first = match tokens.get(*pos) {
+        Some(TokenTree::Punct(p)) if p.as_char() == '?' || p.as_char() == '*' => {
+            *pos += 1;
+            First::Any
+        }
+        Some(TokenTree::Punct(p)) if p.as_char() == '+' => {
+            *pos += 1;
+            first
+        }
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+            *pos += 1;
+            // `{min, max}` needs at least one repetition unless `min` is zero
+            match g.stream().into_iter().next() {
+                Some(TokenTree::Literal(l)) if l.to_string() != "0" => first,
+                _ => First::Any,
+            }
+        }
+        _ => first,
+    };
+
+    if let Some(TokenTree::Punct(p)) = tokens.get(*pos) {
+        if p.as_char() == '@' {
+            *pos += 2;
+        }
+    }
+
+    return first;
+}
+
+fn class(stream: TokenStream) -> First {
+    let mut patterns = Vec::new();
+    let tokens = stream.into_iter().collect::<Vec<_>>();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let start = match &tokens[i] {
+            // bytes are not characters
+            TokenTree::Literal(l) if l.to_string().starts_with("b'") => return First::Any,
+            TokenTree::Literal(l) => l,
+            _ => return First::Any,
+        };
+
+        match (tokens.get(i + 1), tokens.get(i + 2)) {
+            (Some(TokenTree::Punct(p)), Some(TokenTree::Literal(end))) if p.as_char() == '-' => {
+                patterns.push(quote::quote!(#start..=#end));
+                i += 3;
+            }
+            _ => {
+                patterns.push(quote::quote!(#start));
+                i += 1;
+            }
+        }
+    }
+
+    if patterns.is_empty() {
+        return First::Chars(quote::quote!(false));
+    }
+
+    return First::Chars(quote::quote!(matches!(_c, #(#patterns)|*)));
+}
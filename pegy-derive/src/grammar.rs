@@ -1,14 +1,19 @@
 use std::str::FromStr;
 
-use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
 use quote::{ToTokens, TokenStreamExt};
 
-#[derive(Default)]
-pub struct Parser;
+use crate::Derive;
 
-impl syn::parse::Parser for Parser {
+pub struct Parser {
+    derive: Derive,
+    /// the token types matched by the grammar
+    pub tokens: Vec<TokenStream>,
+}
+
+impl syn::parse::Parser for &mut Parser {
     type Output = TokenStream;
-    fn parse2(mut self, tokens: proc_macro2::TokenStream) -> syn::Result<Self::Output> {
+    fn parse2(self, tokens: proc_macro2::TokenStream) -> syn::Result<Self::Output> {
         let tokens = tokens.into_iter().collect::<Vec<TokenTree>>();
         let mut pos = 0;
         let stream = self.parse_alternative(&tokens, &mut pos, false)?;
@@ -18,6 +23,13 @@ impl syn::parse::Parser for Parser {
 }
 
 impl Parser {
+    pub(crate) fn new(derive: Derive) -> Self {
+        return Self {
+            derive: derive,
+            tokens: Vec::new(),
+        };
+    }
+
     pub fn parse_alternative(
         &mut self,
         tokens: &[TokenTree],
@@ -29,6 +41,7 @@ impl Parser {
         }
 
         let mut stream = TokenStream::new();
+        let record_error = record_error();
 
         while *pos < tokens.len() {
             let terms = self.parse_terms(tokens, pos, is_silent)?;
@@ -51,7 +64,7 @@ impl Parser {
                     if match #terms{
                         Ok(_) => true,
                         Err(e) => {
-                            _error = Some(e);
+                            #record_error
                             false
                         }
                     }{
@@ -61,10 +74,10 @@ impl Parser {
                 });
             } else {
                 stream.extend(quote::quote! {
-                    else if match {src.set_position(_start); #terms}{
+                    else if match {src.set_position(_start); src.truncate_recorded(_recorded); #terms}{
                         Ok(_) => true,
                         Err(e) => {
-                            _error = Some(e);
+                            #record_error
                             false
                         }
                     }{
@@ -78,6 +91,7 @@ impl Parser {
         stream.extend(quote::quote! {
             else{
                 src.set_position(_start);
+                src.truncate_recorded(_recorded);
                 Err(_error.take().unwrap())
             }
         });
@@ -85,6 +99,7 @@ impl Parser {
         return Ok(quote::quote! {
             {
                 let _start = src.current_position();
+                let _recorded = src.recorded_len();
                 #stream
             }
         });
@@ -183,27 +198,91 @@ impl Parser {
                     };
                 }
                 Delimiter::Bracket => {
+                    if is_silent {
+                        if let Some(scan) = self.parse_class_scan(g.stream(), tokens, pos)? {
+                            return self.parse_label(tokens, pos, scan);
+                        }
+                    }
                     stream = self.parse_character_class(g.stream())?;
                 }
             },
+            // `_` is an identifier, not a punctuation
+            TokenTree::Ident(i) if i == "_" => {
+                stream = self.parse_term(tokens, pos, true)?;
+            }
+            TokenTree::Ident(i) if i == "recover" && is_parenthesis(tokens.get(*pos)) => {
+                let g = match &tokens[*pos] {
+                    TokenTree::Group(g) => g,
+                    _ => unreachable!(),
+                };
+                *pos += 1;
+
+                stream = self.parse_recover(g, is_silent)?;
+            }
+            TokenTree::Ident(i) if i == "token" && is_parenthesis(tokens.get(*pos)) => {
+                let g = match &tokens[*pos] {
+                    TokenTree::Group(g) => g,
+                    _ => unreachable!(),
+                };
+                *pos += 1;
+
+                let (ty, pattern, label) = parse_token_pattern(g)?;
+                let matched = quote::quote!(::pegy::MatchToken::match_token(
+                    src,
+                    |_t| matches!(_t, #pattern)
+                ));
+                self.tokens.push(ty);
+
+                stream = quote::quote_spanned! { i.span() =>
+                    match #matched{
+                        Some(_t) => Ok(_t),
+                        None => {
+                            let _pos = src.current_position();
+                            Err(::pegy::Error::new(::pegy::Span::new(_pos, _pos), concat!("expected ", #label)).with_expected(#label))
+                        }
+                    }
+                };
+            }
             TokenTree::Ident(i) => {
                 let mut id = i.to_token_stream();
 
+                // a path, e.g. `pegy::util::ANY`
+                while is_path_sep(tokens, *pos) {
+                    let segment = match tokens.get(*pos + 2) {
+                        Some(TokenTree::Ident(i)) => i,
+                        _ => {
+                            return Err(syn::Error::new(tokens[*pos].span(), "expected identifier"))
+                        }
+                    };
+                    id.append(tokens[*pos].clone());
+                    id.append(tokens[*pos + 1].clone());
+                    id.append(segment.clone());
+                    *pos += 3;
+                }
+
                 if let Some(TokenTree::Punct(p)) = tokens.get(*pos) {
                     if p.as_char() == '<' {
                         *pos += 1;
                         id.append(TokenTree::Punct(p.clone()));
 
                         let mut closed = false;
+                        // nested generics, e.g. `LengthPrefixed<BE<u16>, Body>`
+                        let mut depth = 1;
 
                         while let Some(t) = tokens.get(*pos) {
                             *pos += 1;
                             id.append(t.clone());
                             match t {
                                 TokenTree::Punct(p) => {
+                                    if p.as_char() == '<' {
+                                        depth += 1;
+                                    }
                                     if p.as_char() == '>' {
-                                        closed = true;
-                                        break;
+                                        depth -= 1;
+                                        if depth == 0 {
+                                            closed = true;
+                                            break;
+                                        }
                                     }
                                 }
                                 _ => {}
@@ -216,28 +295,53 @@ impl Parser {
                     };
                 };
 
-                stream =
-                    quote::quote_spanned!(i.span() => <#id as ::pegy::Parse>::parse(src).await);
+                let call = self.derive.rule_call(&id);
+
+                stream = quote::quote_spanned!(i.span() => #call);
             }
             TokenTree::Literal(lit) => {
                 let l = lit.to_string();
 
-                if l.starts_with('"') {
+                if l.starts_with("b\"") || l.starts_with("br") {
+                    let matched = self.derive.source_call("match_bytes", quote::quote!(#lit));
                     stream = quote::quote_spanned! { lit.span() =>
-                        if src.match_str(#lit).await{
+                        if #matched{
                             Ok(#lit)
                         } else{
                             let _pos = src.current_position();
-                            Err(::pegy::Error::new(::pegy::Span::new(_pos, _pos), concat!("expected string literal '", #lit, "'")))
+                            Err(::pegy::Error::new(::pegy::Span::new(_pos, _pos), concat!("expected ", stringify!(#lit))).with_expected(stringify!(#lit)))
+                        }
+                    };
+                } else if l.starts_with("b'") {
+                    let matched = self
+                        .derive
+                        .source_call("match_bytes", quote::quote!(&[#lit]));
+                    stream = quote::quote_spanned! { lit.span() =>
+                        if #matched{
+                            Ok(#lit)
+                        } else{
+                            let _pos = src.current_position();
+                            Err(::pegy::Error::new(::pegy::Span::new(_pos, _pos), concat!("expected ", stringify!(#lit))).with_expected(stringify!(#lit)))
+                        }
+                    };
+                } else if l.starts_with('"') {
+                    let matched = self.derive.source_call("match_str", quote::quote!(#lit));
+                    stream = quote::quote_spanned! { lit.span() =>
+                        if #matched{
+                            Ok(#lit)
+                        } else{
+                            let _pos = src.current_position();
+                            Err(::pegy::Error::new(::pegy::Span::new(_pos, _pos), concat!("expected ", stringify!(#lit))).with_expected(stringify!(#lit)))
                         }
                     };
                 } else {
+                    let matched = self.derive.source_call("match_char", quote::quote!(#lit));
                     stream = quote::quote_spanned! {lit.span() =>
-                        if src.match_char(#lit).await{
+                        if #matched{
                             Ok(#lit)
                         } else{
                             let _pos = src.current_position();
-                            Err(::pegy::Error::new(::pegy::Span::new(_pos, _pos), concat!("expected character '", #lit, "'")))
+                            Err(::pegy::Error::new(::pegy::Span::new(_pos, _pos), concat!("expected ", stringify!(#lit))).with_expected(stringify!(#lit)))
                         }
                     };
                 }
@@ -294,15 +398,141 @@ impl Parser {
                         }
 
                     };
-                } else if p.as_char() == '_' {
-                    stream = self.parse_term(tokens, pos, true)?;
                 } else {
                     return Err(syn::Error::new(p.span(), "unexpected token"));
                 }
             }
         };
 
-        return Ok(self.parse_quantifier(tokens, pos, is_silent, stream)?);
+        let stream = self.parse_quantifier(tokens, pos, is_silent, stream)?;
+
+        return self.parse_label(tokens, pos, stream);
+    }
+
+    /// parses the arguments of `recover(term, until = alternatives)`
+    pub fn parse_recover(
+        &mut self,
+        group: &proc_macro2::Group,
+        is_silent: bool,
+    ) -> syn::Result<TokenStream> {
+        let tokens = group.stream().into_iter().collect::<Vec<TokenTree>>();
+        let mut pos = 0;
+
+        if tokens.is_empty() {
+            return Err(syn::Error::new(group.span(), "expected term to recover"));
+        }
+
+        let term = self.parse_term(&tokens, &mut pos, is_silent)?;
+
+        match tokens.get(pos) {
+            Some(TokenTree::Punct(p)) if p.as_char() == ',' => pos += 1,
+            Some(t) => {
+                return Err(syn::Error::new(
+                    t.span(),
+                    "expected ',', use a group to recover a sequence of terms",
+                ))
+            }
+            None => return Err(syn::Error::new(group.span(), "expected 'until = ...'")),
+        };
+
+        match tokens.get(pos) {
+            Some(TokenTree::Ident(i)) if i == "until" => pos += 1,
+            _ => return Err(syn::Error::new(group.span(), "expected 'until = ...'")),
+        };
+        match tokens.get(pos) {
+            Some(TokenTree::Punct(p)) if p.as_char() == '=' => pos += 1,
+            _ => return Err(syn::Error::new(group.span(), "expected 'until = ...'")),
+        };
+
+        if pos == tokens.len() {
+            return Err(syn::Error::new(
+                group.span(),
+                "expected synchronisation terms",
+            ));
+        }
+
+        let sync = self.parse_alternative(&tokens[pos..], &mut 0, true)?;
+        let peek = self.derive.source_call("peek", TokenStream::new());
+
+        return Ok(quote::quote_spanned! { group.span() =>
+            {
+                let _recover_start = src.current_position();
+                let _recover_recorded = src.recorded_len();
+                match #term{
+                    Ok(v) => Ok(v),
+                    Err(e) => {
+                        src.set_position(_recover_start);
+                        src.truncate_recorded(_recover_recorded);
+
+                        match src.record_error(e){
+                            Ok(()) => {
+                                // failed synchronisation attempts are not errors
+                                let _saved_error = _error.take();
+
+                                // skip until the synchronisation terms match, without consuming them
+                                loop{
+                                    let _pos = src.current_position();
+                                    let _synced = #sync;
+                                    if _synced.is_ok(){
+                                        src.set_position(_pos);
+                                        break;
+                                    }
+                                    match #peek{
+                                        Some(_ch) => src.set_position(_pos + _ch.length),
+                                        None => break,
+                                    }
+                                }
+
+                                _error = _saved_error;
+                                Ok(::core::default::Default::default())
+                            }
+                            Err(e) => Err(e),
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// parses an optional `@ "label"` behind a term
+    pub fn parse_label(
+        &mut self,
+        tokens: &[TokenTree],
+        pos: &mut usize,
+        term: TokenStream,
+    ) -> syn::Result<TokenStream> {
+        let p = match tokens.get(*pos) {
+            Some(TokenTree::Punct(p)) if p.as_char() == '@' => p,
+            _ => return Ok(term),
+        };
+        *pos += 1;
+
+        let label = match tokens.get(*pos) {
+            Some(TokenTree::Literal(l)) if l.to_string().starts_with('"') => l,
+            _ => {
+                return Err(syn::Error::new(
+                    p.span(),
+                    "expected string literal behind '@'",
+                ))
+            }
+        };
+        *pos += 1;
+
+        return Ok(quote::quote! {
+            {
+                let _label_start = src.current_position();
+                match #term{
+                    Ok(v) => Ok(v),
+                    Err(e) => {
+                        if e.span.0 == _label_start{
+                            Err(e.label(#label))
+                        } else{
+                            Err(e)
+                        }
+                    }
+                }
+            }
+        });
     }
 
     pub fn parse_quantifier(
@@ -312,6 +542,10 @@ impl Parser {
         is_silent: bool,
         term: TokenStream,
     ) -> syn::Result<TokenStream> {
+        // the error that ends a repetition is kept, it is reported
+        // if the rule fails further into the input.
+        let record_error = record_error();
+
         if let Some(TokenTree::Punct(p)) = tokens.get(*pos) {
             if p.as_char() == '+' {
                 *pos += 1;
@@ -320,8 +554,20 @@ impl Parser {
                         {
                             let _start = src.current_position();
                             let mut _i:usize = 0;
-                            while let Ok(_value) = #term{
+                            loop{
+                                let _iter_start = src.current_position();
+                                let _value = match #term{
+                                    Ok(v) => v,
+                                    Err(e) => {
+                                        #record_error
+                                        break;
+                                    }
+                                };
                                 _i += 1;
+                                // a term that matches nothing would repeat forever
+                                if src.current_position() == _iter_start{
+                                    break;
+                                }
                             };
 
                             if _i == 0{
@@ -336,8 +582,20 @@ impl Parser {
                     {
                         let _start = src.current_position();
                         let mut _v = Vec::new();
-                        while let Ok(_value) = #term{
+                        loop{
+                                let _iter_start = src.current_position();
+                                let _value = match #term{
+                                    Ok(v) => v,
+                                    Err(e) => {
+                                        #record_error
+                                        break;
+                                    }
+                                };
                             _v.push(_value);
+                            // a term that matches nothing would repeat forever
+                            if src.current_position() == _iter_start{
+                                break;
+                            }
                         };
 
                         if _v.len() == 0{
@@ -355,7 +613,20 @@ impl Parser {
                 if is_silent {
                     return Ok(quote::quote! {
                         {
-                            while let Ok(_value) = #term{};
+                            loop{
+                                let _iter_start = src.current_position();
+                                let _value = match #term{
+                                    Ok(v) => v,
+                                    Err(e) => {
+                                        #record_error
+                                        break;
+                                    }
+                                };
+                                // a term that matches nothing would repeat forever
+                                if src.current_position() == _iter_start{
+                                    break;
+                                }
+                            };
                             Ok::<(), ::pegy::Error>(())
                         }
                     });
@@ -363,8 +634,20 @@ impl Parser {
                 return Ok(quote::quote! {
                     {
                         let mut _v = Vec::new();
-                        while let Ok(_value) = #term{
+                        loop{
+                                let _iter_start = src.current_position();
+                                let _value = match #term{
+                                    Ok(v) => v,
+                                    Err(e) => {
+                                        #record_error
+                                        break;
+                                    }
+                                };
                             _v.push(_value);
+                            // a term that matches nothing would repeat forever
+                            if src.current_position() == _iter_start{
+                                break;
+                            }
                         };
                         Ok::<_, ::pegy::Error>(_v)
                     }
@@ -376,18 +659,22 @@ impl Parser {
 
                 if is_silent {
                     return Ok(quote::quote! {
-                        if let Ok(_v) = #term{
-                            Ok::<Option<()>, ::pegy::Error>(Some(()))
-                        } else{
-                            Ok(None)
+                        match #term{
+                            Ok(_) => Ok::<Option<()>, ::pegy::Error>(Some(())),
+                            Err(e) => {
+                                #record_error
+                                Ok(None)
+                            }
                         }
                     });
                 }
                 return Ok(quote::quote! {
-                    if let Ok(v) = #term{
-                        Ok::<_, ::pegy::Error>(Some(v))
-                    } else{
-                        Ok(None)
+                    match #term{
+                        Ok(v) => Ok::<_, ::pegy::Error>(Some(v)),
+                        Err(e) => {
+                            #record_error
+                            Ok(None)
+                        }
                     }
                 });
             }
@@ -438,12 +725,24 @@ impl Parser {
                         {
                             let _start = src.current_position();
                             let mut _i:usize = 0;
-                            while let Ok(_value) = #term{
+                            loop{
+                                let _iter_start = src.current_position();
+                                let _value = match #term{
+                                    Ok(v) => v,
+                                    Err(e) => {
+                                        #record_error
+                                        break;
+                                    }
+                                };
                                 _i += 1;
 
                                 if _i >= #max{
                                     break;
                                 }
+                                // a term that matches nothing would repeat forever
+                                if src.current_position() == _iter_start && _i >= #min{
+                                    break;
+                                }
                             };
 
                             if _i < #min{
@@ -458,12 +757,24 @@ impl Parser {
                     {
                         let _start = src.current_position();
                         let mut _v = Vec::new();
-                        while let Ok(_value) = #term{
+                        loop{
+                                let _iter_start = src.current_position();
+                                let _value = match #term{
+                                    Ok(v) => v,
+                                    Err(e) => {
+                                        #record_error
+                                        break;
+                                    }
+                                };
                             _v.push(_value);
 
                             if _v.len() >= #max{
                                 break;
                             }
+                            // a term that matches nothing would repeat forever
+                            if src.current_position() == _iter_start && _v.len() >= #min{
+                                break;
+                            }
                         };
 
                         if _v.len() < #min{
@@ -489,36 +800,7 @@ impl Parser {
     }
 
     pub fn parse_character_class(&mut self, stream: TokenStream) -> syn::Result<TokenStream> {
-        let mut ranges = Vec::new();
-        let mut last_char: Option<proc_macro2::Literal> = None;
-        let mut is_continue = false;
-
-        for token in stream.into_iter() {
-            match token {
-                TokenTree::Literal(l) => {
-                    if let Some(last) = last_char.take() {
-                        if is_continue {
-                            ranges.push((last, l))
-                        } else {
-                            ranges.push((last.clone(), last));
-                            last_char = Some(l);
-                        }
-                    } else {
-                        last_char = Some(l);
-                    }
-                }
-                TokenTree::Punct(p) => {
-                    if p.as_char() != '-' {
-                        return Err(syn::Error::new(p.span(), "expected '-' or character"));
-                    }
-                    if last_char.is_none() {
-                        return Err(syn::Error::new(p.span(), "expected character"));
-                    }
-                    is_continue = true;
-                }
-                _ => return Err(syn::Error::new(token.span(), "unexpected token")),
-            }
-        }
+        let ranges = parse_class_ranges(stream)?;
 
         if ranges.is_empty() {
             return Ok(quote::quote! {{
@@ -528,30 +810,262 @@ impl Parser {
         }
 
         let mut stream = TokenStream::new();
+        let match_range = if is_byte_class(&ranges)? {
+            "match_byte_range"
+        } else {
+            "match_char_range"
+        };
 
-        for (start, end) in ranges {
+        for (start, end) in &ranges {
+            let matched = self
+                .derive
+                .source_call(match_range, quote::quote!(#start..=#end));
             if stream.is_empty() {
                 stream.extend(quote::quote_spanned! { start.span() =>
-                    if let Some(_ch) = src.match_char_range(#start..=#end).await{
+                    if let Some(_ch) = #matched{
                         Ok(_ch)
                     }
                 })
             } else {
                 stream.extend(quote::quote_spanned! { start.span() =>
-                    else if let Some(_ch) = src.match_char_range(#start..=#end).await{
+                    else if let Some(_ch) = #matched{
                         Ok(_ch)
                     }
                 })
             }
         }
 
-        stream.extend(quote::quote!{
+        let error = class_error(&ranges);
+
+        stream.extend(quote::quote! {
             else{
-                let _pos = src.current_position();
-                Err(::pegy::Error::new(::pegy::Span::new(_pos, _pos), "failed to match character class"))
+                Err(#error)
             }
         });
 
         return Ok(stream);
     }
+
+    /// parses a quiet `[...]*` or `[...]+` into a single `take_while` scan,
+    /// returns `None` if the class is not followed by one of the quantifiers.
+    pub fn parse_class_scan(
+        &mut self,
+        stream: TokenStream,
+        tokens: &[TokenTree],
+        pos: &mut usize,
+    ) -> syn::Result<Option<TokenStream>> {
+        let quantifier = match tokens.get(*pos) {
+            Some(TokenTree::Punct(p)) if p.as_char() == '*' || p.as_char() == '+' => p.as_char(),
+            _ => return Ok(None),
+        };
+
+        let ranges = parse_class_ranges(stream)?;
+
+        // bytes are matched one at a time
+        if ranges.is_empty() || is_byte_class(&ranges)? {
+            return Ok(None);
+        }
+
+        *pos += 1;
+
+        let mut patterns = TokenStream::new();
+        for (i, (start, end)) in ranges.iter().enumerate() {
+            if i != 0 {
+                patterns.extend(quote::quote!(|));
+            }
+            patterns.extend(quote::quote!(#start..=#end));
+        }
+
+        let error = class_error(&ranges);
+        // the error that ends the repetition is kept, like in a loop
+        let record_error = record_error();
+
+        let check = if quantifier == '+' {
+            quote::quote! {
+                if _span.is_empty(){
+                    Err(::pegy::Error::new(::pegy::Span::new(_span.0, _span.0), "expected at least one repetition"))
+                } else{
+                    Ok(())
+                }
+            }
+        } else {
+            quote::quote!(Ok::<(), ::pegy::Error>(()))
+        };
+
+        let scan = self.derive.source_call(
+            "take_while",
+            quote::quote!(|_c: char| matches!(_c, #patterns)),
+        );
+
+        return Ok(Some(quote::quote! {
+            {
+                let _span = #scan;
+                let e = #error;
+                #record_error
+                #check
+            }
+        }));
+    }
+}
+
+/// parses the ranges of a character class, single characters are ranges of one
+fn parse_class_ranges(
+    stream: TokenStream,
+) -> syn::Result<Vec<(proc_macro2::Literal, proc_macro2::Literal)>> {
+    let mut ranges = Vec::new();
+    let mut last_char: Option<proc_macro2::Literal> = None;
+    let mut is_continue = false;
+
+    for token in stream.into_iter() {
+        match token {
+            TokenTree::Literal(l) => {
+                if let Some(last) = last_char.take() {
+                    if is_continue {
+                        ranges.push((last, l));
+                        is_continue = false;
+                    } else {
+                        ranges.push((last.clone(), last));
+                        last_char = Some(l);
+                    }
+                } else {
+                    last_char = Some(l);
+                }
+            }
+            TokenTree::Punct(p) => {
+                if p.as_char() != '-' {
+                    return Err(syn::Error::new(p.span(), "expected '-' or character"));
+                }
+                if last_char.is_none() {
+                    return Err(syn::Error::new(p.span(), "expected character"));
+                }
+                is_continue = true;
+            }
+            _ => return Err(syn::Error::new(token.span(), "unexpected token")),
+        }
+    }
+
+    if let Some(last) = last_char {
+        if is_continue {
+            return Err(syn::Error::new(last.span(), "missing end of range"));
+        }
+        ranges.push((last.clone(), last));
+    }
+
+    return Ok(ranges);
+}
+
+/// returns true if `tokens[pos]` starts a `::`
+fn is_path_sep(tokens: &[TokenTree], pos: usize) -> bool {
+    match (tokens.get(pos), tokens.get(pos + 1)) {
+        (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b))) => {
+            a.as_char() == ':' && a.spacing() == Spacing::Joint && b.as_char() == ':'
+        }
+        _ => false,
+    }
+}
+
+/// parses the pattern of a `token(...)` term, e.g. `Token::Ident(_)`.
+/// Returns the token type, the pattern and its label.
+fn parse_token_pattern(
+    group: &proc_macro2::Group,
+) -> syn::Result<(TokenStream, TokenStream, String)> {
+    let tokens = group.stream().into_iter().collect::<Vec<TokenTree>>();
+    let mut pos = 1;
+    let mut ty_end = 0;
+
+    if !matches!(tokens.first(), Some(TokenTree::Ident(_))) {
+        return Err(syn::Error::new(group.span(), "expected token pattern"));
+    }
+
+    while is_path_sep(&tokens, pos) {
+        if !matches!(tokens.get(pos + 2), Some(TokenTree::Ident(_))) {
+            return Err(syn::Error::new(
+                tokens[pos].span(),
+                "expected token variant",
+            ));
+        }
+        ty_end = pos;
+        pos += 3;
+    }
+
+    if ty_end == 0 {
+        return Err(syn::Error::new(
+            tokens[0].span(),
+            "expected token variant, e.g. `Token::Ident(_)`",
+        ));
+    }
+
+    let ty = tokens[..ty_end].iter().cloned().collect();
+    // the path is printed without the spaces of `TokenStream::to_string`
+    let label = tokens.iter().map(|t| t.to_string()).collect();
+
+    return Ok((ty, group.stream(), label));
+}
+
+/// returns true if the class is made of byte literals, e.g. `[b'0'-b'9']`
+fn is_byte_class(ranges: &[(proc_macro2::Literal, proc_macro2::Literal)]) -> syn::Result<bool> {
+    let is_byte = |l: &proc_macro2::Literal| l.to_string().starts_with("b'");
+    let first = match ranges.first() {
+        Some((start, _)) => is_byte(start),
+        None => return Ok(false),
+    };
+
+    for (start, end) in ranges {
+        for l in [start, end] {
+            if is_byte(l) != first {
+                return Err(syn::Error::new(
+                    l.span(),
+                    "a class cannot mix bytes and characters",
+                ));
+            }
+        }
+    }
+
+    return Ok(first);
+}
+
+/// the error of a character class that failed to match at the current position
+fn class_error(ranges: &[(proc_macro2::Literal, proc_macro2::Literal)]) -> TokenStream {
+    let mut expected = TokenStream::new();
+    let mut labels = Vec::new();
+
+    for (start, end) in ranges {
+        let label = if start.to_string() == end.to_string() {
+            start.to_string()
+        } else {
+            format!("{}..={}", start, end)
+        };
+        expected.extend(quote::quote!(.with_expected(#label)));
+        labels.push(label);
+    }
+
+    let msg = match labels.split_last() {
+        Some((last, [])) => format!("expected {}", last),
+        Some((last, rest)) => format!("expected {} or {}", rest.join(", "), last),
+        None => String::from("failed to match character class"),
+    };
+
+    return quote::quote! {
+        {
+            let _pos = src.current_position();
+            ::pegy::Error::new(::pegy::Span::new(_pos, _pos), #msg)#expected
+        }
+    };
+}
+
+/// merges the error `e` into the farthest error `_error` of the rule
+fn record_error() -> TokenStream {
+    quote::quote! {
+        _error = Some(match _error.take(){
+            Some(_prev) => _prev.merge(e),
+            None => e
+        });
+    }
+}
+
+fn is_parenthesis(token: Option<&TokenTree>) -> bool {
+    match token {
+        Some(TokenTree::Group(g)) => g.delimiter() == Delimiter::Parenthesis,
+        _ => false,
+    }
 }
@@ -3,25 +3,126 @@ use std::str::FromStr;
 use proc_macro2::{Punct, Spacing, TokenStream, TokenTree};
 use quote::{ToTokens, TokenStreamExt};
 
+mod attr;
 mod grammar;
 mod ast;
 mod ast_transform;
+mod first;
+mod send;
+mod sync;
 
-#[proc_macro_derive(Parse, attributes(grammar))]
+#[proc_macro_derive(Parse, attributes(grammar, parse))]
 pub fn parse_derive(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let re = syn::parse::<syn::Item>(stream);
 
     match re {
         Ok(item) => match item {
-            syn::Item::Struct(s) => compile_struct(s).into(),
-            syn::Item::Enum(e) => compile_enum(e).into(),
+            syn::Item::Struct(s) => compile_struct(&s, Derive::Parse).into(),
+            syn::Item::Enum(e) => compile_enum(&e, Derive::Parse).into(),
             _ => quote::quote!(compile_error!("Parse macro expected struct or enum.")).into(),
         },
         Err(e) => e.into_compile_error().into(),
     }
 }
 
-fn compile_struct(s: syn::ItemStruct) -> proc_macro2::TokenStream {
+/// derives `ParseSync` from the same grammar as `Parse`
+#[proc_macro_derive(ParseSync, attributes(grammar, parse))]
+pub fn parse_sync_derive(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let re = syn::parse::<syn::Item>(stream);
+
+    match re {
+        Ok(item) => match item {
+            syn::Item::Struct(s) => compile_struct(&s, Derive::ParseSync).into(),
+            syn::Item::Enum(e) => compile_enum(&e, Derive::ParseSync).into(),
+            _ => quote::quote!(compile_error!("ParseSync macro expected struct or enum.")).into(),
+        },
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// derives `ParseSend` from the same grammar as `Parse`
+#[proc_macro_derive(ParseSend, attributes(grammar, parse))]
+pub fn parse_send_derive(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let re = syn::parse::<syn::Item>(stream);
+
+    match re {
+        Ok(item) => match item {
+            syn::Item::Struct(s) => compile_struct(&s, Derive::ParseSend).into(),
+            syn::Item::Enum(e) => compile_enum(&e, Derive::ParseSend).into(),
+            _ => quote::quote!(compile_error!("ParseSend macro expected struct or enum.")).into(),
+        },
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// the trait implemented by a derive
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Derive {
+    Parse,
+    ParseSync,
+    ParseSend,
+}
+
+impl Derive {
+    /// a call of the source method `method` in the body of the trait,
+    /// e.g. `src.match_str_sync("a")` for `ParseSync`
+    fn source_call(self, method: &str, args: TokenStream) -> TokenStream {
+        let suffix = match self {
+            Derive::Parse => "",
+            Derive::ParseSync => "_sync",
+            Derive::ParseSend => "_send",
+        };
+        let method = syn::Ident::new(
+            &format!("{}{}", method, suffix),
+            proc_macro2::Span::call_site(),
+        );
+
+        if self == Derive::ParseSync {
+            return quote::quote!(src.#method(#args));
+        }
+        return quote::quote!(src.#method(#args).await);
+    }
+
+    /// a call of the rule `ty` in the body of the trait,
+    /// the tokens are those of the source
+    fn rule_call(self, ty: &TokenStream) -> TokenStream {
+        match self {
+            Derive::Parse => quote::quote!(<#ty as ::pegy::Parse<_>>::parse(src).await),
+            Derive::ParseSync => quote::quote!(<#ty as ::pegy::ParseSync<_>>::parse_sync(src)),
+            Derive::ParseSend => {
+                quote::quote!(<#ty as ::pegy::ParseSend<_>>::parse_send(src).await)
+            }
+        }
+    }
+}
+
+/// the tokens of the sources a rule parses.
+///
+/// These are given by `#[parse(token = ...)]` or else by the token patterns of the grammar,
+/// a grammar without token patterns parses text.
+fn token_type(options: &attr::RuleOptions, parser: &grammar::Parser) -> syn::Result<TokenStream> {
+    if let Some(token) = &options.token {
+        return Ok(token.to_token_stream());
+    }
+
+    let first = match parser.tokens.first() {
+        Some(t) => t,
+        None => return Ok(quote::quote!(::core::convert::Infallible)),
+    };
+
+    for token in &parser.tokens {
+        if token.to_string() != first.to_string() {
+            return Err(syn::Error::new_spanned(
+                token,
+                "a grammar matches tokens of a single type",
+            ));
+        }
+    }
+
+    return Ok(first.clone());
+}
+
+fn compile_struct(s: &syn::ItemStruct, derive: Derive) -> proc_macro2::TokenStream {
     let mut declares = TokenStream::new();
 
     let ident = &s.ident;
@@ -40,13 +141,16 @@ fn compile_struct(s: syn::ItemStruct) -> proc_macro2::TokenStream {
     }
 
     let mut grammar = TokenStream::new();
+    let mut grammar_attr = None;
+    let mut parser = grammar::Parser::new(derive);
     for attr in &s.attrs {
         if attr.path().is_ident("grammar") {
-            let re = attr.parse_args_with(grammar::Parser::default());
+            let re = attr.parse_args_with(&mut parser);
 
             match re {
                 Ok(g) => {
                     grammar = g;
+                    grammar_attr = Some(attr);
                     break;
                 }
                 Err(e) => return e.into_compile_error(),
@@ -58,6 +162,22 @@ fn compile_struct(s: syn::ItemStruct) -> proc_macro2::TokenStream {
         return syn::Error::new(ident.span(), "missing grammar").into_compile_error();
     }
 
+    let options = match attr::RuleOptions::from_attrs(&s.attrs) {
+        Ok(o) => o,
+        Err(e) => return e.into_compile_error(),
+    };
+    let token = match token_type(&options, &parser) {
+        Ok(t) => t,
+        Err(e) => return e.into_compile_error(),
+    };
+    let map_error = options.map_error();
+    let emit_warning = options.emit_warning();
+
+    if options.error {
+        return syn::Error::new(ident.span(), "`error` is only allowed on enum variants")
+            .into_compile_error();
+    }
+
     let mut construction = TokenStream::new();
 
     match &s.fields {
@@ -87,30 +207,74 @@ fn compile_struct(s: syn::ItemStruct) -> proc_macro2::TokenStream {
         }
     }
 
-    return quote::quote! {
-        impl ::pegy::Parse for #ident{
-            type Output = Self;
-            async fn parse<S: ::pegy::Source>(src: &mut S) -> Result<Self::Output, ::pegy::Error>{
-                #declares;
-                let _start = src.current_position();
-                let mut _error = None;
+    let body = quote::quote! {
+        #declares;
+        let _start = src.current_position();
+        let _recorded = src.recorded_len();
+        let mut _error: Option<::pegy::Error> = None;
 
-                let re = #grammar;
+        let re = #grammar;
 
-                match re{
-                    Ok(_) => Ok(#construction),
-                    Err(e) => {
-                        src.set_position(_start);
-                        Err(e)
-                    }
-                }
+        match re{
+            Ok(_) => {
+                #emit_warning
+                Ok(#construction)
+            }
+            Err(e) => {
+                src.set_position(_start);
+                src.truncate_recorded(_recorded);
+                Err(#map_error)
+            }
+        }
+    };
+
+    match derive {
+        Derive::Parse => {}
+        Derive::ParseSync => return sync::impl_parse_sync(ident, &s.generics, &token, body),
+        Derive::ParseSend => return send::impl_parse_send(ident, &s.generics, &token, body),
+    }
+
+    let first = grammar_first(grammar_attr, &token);
+    let (impl_generics, ty_generics, where_clause) = s.generics.split_for_impl();
+
+    return quote::quote! {
+        impl #impl_generics ::pegy::Parse<#token> for #ident #ty_generics #where_clause{
+            type Output = Self;
+            async fn parse<S: ::pegy::MatchToken<#token>>(src: &mut S) -> Result<Self::Output, ::pegy::Error>{
+                #body
+            }
+            fn may_start_with(_c: char) -> bool {
+                #first
             }
         }
     };
 }
 
-fn compile_enum(e: syn::ItemEnum) -> TokenStream {
+/// the body of `may_start_with` for the grammar of an attribute
+fn grammar_first(attr: Option<&syn::Attribute>, token: &TokenStream) -> TokenStream {
+    match attr.map(|a| a.meta.require_list()) {
+        Some(Ok(list)) => first::may_start_with(list.tokens.clone(), token),
+        _ => quote::quote!(true),
+    }
+}
+
+fn compile_enum(e: &syn::ItemEnum, derive: Derive) -> TokenStream {
+    let options = match attr::RuleOptions::from_attrs(&e.attrs) {
+        Ok(o) => o,
+        Err(e) => return e.into_compile_error(),
+    };
+    let map_error = options.map_error();
+    let emit_warning = options.emit_warning();
+
+    if options.error {
+        return syn::Error::new(e.ident.span(), "`error` is only allowed on enum variants")
+            .into_compile_error();
+    }
+
     let mut variants = TokenStream::new();
+    let mut error_variant: Option<TokenStream> = None;
+    let mut firsts = Vec::new();
+    let mut parser = grammar::Parser::new(derive);
 
     for varient in &e.variants {
         let mut declares = TokenStream::new();
@@ -133,13 +297,15 @@ fn compile_enum(e: syn::ItemEnum) -> TokenStream {
         }
 
         let mut grammar = TokenStream::new();
+        let mut grammar_attr = None;
         for attr in &varient.attrs {
             if attr.path().is_ident("grammar") {
-                let re = attr.parse_args_with(grammar::Parser::default());
+                let re = attr.parse_args_with(&mut parser);
 
                 match re {
                     Ok(g) => {
                         grammar = g;
+                        grammar_attr = Some(attr);
                         break;
                     }
                     Err(e) => return e.into_compile_error(),
@@ -151,6 +317,45 @@ fn compile_enum(e: syn::ItemEnum) -> TokenStream {
             return syn::Error::new(ident.span(), "missing grammar").into_compile_error();
         }
 
+        let variant_options = match attr::RuleOptions::from_attrs(&varient.attrs) {
+            Ok(o) => o,
+            Err(e) => return e.into_compile_error(),
+        };
+        let map_variant_error = variant_options.map_error();
+        let emit_variant_warning = variant_options.emit_warning();
+
+        if variant_options.error {
+            if error_variant.is_some() {
+                return syn::Error::new(ident.span(), "duplicate error variant")
+                    .into_compile_error();
+            }
+
+            let construction = match &varient.fields {
+                syn::Fields::Named(n) if n.named.len() == 1 => {
+                    let field = &n.named[0].ident;
+                    quote::quote!(Self::#ident{#field: _span})
+                }
+                syn::Fields::Unnamed(n) if n.unnamed.len() == 1 => {
+                    quote::quote!(Self::#ident(_span))
+                }
+                syn::Fields::Unit => quote::quote!(Self::#ident),
+                _ => {
+                    return syn::Error::new(
+                        ident.span(),
+                        "error variant must have a single `Span` field or none",
+                    )
+                    .into_compile_error()
+                }
+            };
+
+            error_variant = Some(compile_error_variant(grammar, construction, derive));
+            // the error variant can start anywhere
+            firsts.push(None);
+            continue;
+        }
+
+        firsts.push(grammar_attr);
+
         let mut construction = TokenStream::new();
 
         match &varient.fields {
@@ -182,14 +387,24 @@ fn compile_enum(e: syn::ItemEnum) -> TokenStream {
         variants.extend(quote::quote! {
             {
                 #declares;
-                let mut _error = None;
+                let mut _error: Option<::pegy::Error> = None;
 
                 let re = #grammar;
 
                 match re{
-                    Ok(_) => return Ok(#construction),
-                    Err(_) => {
+                    Ok(_) => {
+                        #emit_variant_warning
+                        #emit_warning
+                        return Ok(#construction)
+                    }
+                    Err(e) => {
                         src.set_position(_start);
+                        src.truncate_recorded(_recorded);
+                        let e = #map_variant_error;
+                        _variant_error = Some(match _variant_error.take(){
+                            Some(_prev) => _prev.merge(e),
+                            None => e
+                        });
                     }
                 }
             };
@@ -197,14 +412,99 @@ fn compile_enum(e: syn::ItemEnum) -> TokenStream {
     }
 
     let enum_id = &e.ident;
+    let body = quote::quote! {
+        let _start = src.current_position();
+        let _recorded = src.recorded_len();
+        let mut _variant_error: Option<::pegy::Error> = None;
+        #variants;
+
+        // report the variant that got furthest, unless none of them made progress
+        let e = match _variant_error{
+            Some(e) if e.span.0 != _start => e,
+            Some(mut e) => {
+                e.msg = concat!("expected ", stringify!(#enum_id)).into();
+                e
+            }
+            None => ::pegy::Error::new(::pegy::Span::new(_start, _start), concat!("expected ", stringify!(#enum_id)))
+        };
+        let e = #map_error;
+        #error_variant
+        Err(e)
+    };
+
+    let token = match token_type(&options, &parser) {
+        Ok(t) => t,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    match derive {
+        Derive::Parse => {}
+        Derive::ParseSync => return sync::impl_parse_sync(enum_id, &e.generics, &token, body),
+        Derive::ParseSend => return send::impl_parse_send(enum_id, &e.generics, &token, body),
+    }
+
+    let firsts = firsts.into_iter().map(|attr| grammar_first(attr, &token));
+    let (impl_generics, ty_generics, where_clause) = e.generics.split_for_impl();
+
     return quote::quote! {
-        impl ::pegy::Parse for #enum_id{
+        impl #impl_generics ::pegy::Parse<#token> for #enum_id #ty_generics #where_clause{
             type Output = Self;
-            async fn parse<S: ::pegy::Source>(src:&mut S) -> ::pegy::Result<Self::Output>{
-                let _start = src.current_position();
-                #variants;
-                return Err(::pegy::Error::new(::pegy::Span::new(_start, _start), concat!("expected ", stringify!(#enum_id))));
+            async fn parse<S: ::pegy::MatchToken<#token>>(src:&mut S) -> ::pegy::Result<Self::Output>{
+                #body
+            }
+            fn may_start_with(_c: char) -> bool {
+                #((#firsts))||*
             }
         }
     };
 }
+
+/// generates the recovery of an enum with an error variant.
+///
+/// The input is skipped until the variant's grammar matches, without
+/// consuming it, and the error is recorded. `construction` builds the
+/// variant from the skipped `_span`. Nothing is recovered if the grammar
+/// matches right away, so that enclosing repetitions can end there.
+fn compile_error_variant(
+    sync: TokenStream,
+    construction: TokenStream,
+    derive: Derive,
+) -> TokenStream {
+    let peek = derive.source_call("peek", TokenStream::new());
+
+    return quote::quote! {
+        let e = {
+            let _recorded = src.recorded_len();
+            let mut _error: Option<::pegy::Error> = None;
+
+            loop{
+                let _pos = src.current_position();
+                let _synced = #sync;
+                src.truncate_recorded(_recorded);
+                if _synced.is_ok(){
+                    src.set_position(_pos);
+                    break;
+                }
+                match #peek{
+                    Some(_ch) => src.set_position(_pos + _ch.length),
+                    None => break,
+                }
+            }
+
+            if src.current_position() == _start{
+                e
+            } else{
+                match src.record_error(e){
+                    Ok(()) => {
+                        let _span = ::pegy::Span::new(_start, src.current_position());
+                        return Ok(#construction);
+                    }
+                    Err(e) => {
+                        src.set_position(_start);
+                        e
+                    }
+                }
+            }
+        };
+    };
+}
@@ -0,0 +1,19 @@
+use proc_macro2::TokenStream;
+
+/// generates `ParseSend` for a rule from the body of its `parse_send`
+pub fn impl_parse_send(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    token: &TokenStream,
+    body: TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    return quote::quote! {
+        impl #impl_generics ::pegy::ParseSend<#token> for #ident #ty_generics #where_clause{
+            async fn parse_send<S: ::pegy::SourceSend + ::pegy::MatchToken<#token>>(src: &mut S) -> ::pegy::Result<Self::Output>{
+                #body
+            }
+        }
+    };
+}
@@ -0,0 +1,19 @@
+use proc_macro2::TokenStream;
+
+/// generates `ParseSync` for a rule from the body of its `parse_sync`
+pub fn impl_parse_sync(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    token: &TokenStream,
+    body: TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    return quote::quote! {
+        impl #impl_generics ::pegy::ParseSync<#token> for #ident #ty_generics #where_clause{
+            fn parse_sync<S: ::pegy::SourceSync + ::pegy::MatchToken<#token>>(src: &mut S) -> ::pegy::Result<Self::Output>{
+                #body
+            }
+        }
+    };
+}
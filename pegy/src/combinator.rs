@@ -0,0 +1,124 @@
+/// implements `Parse`, `ParseSync` and `ParseSend` for a combinator from a single body.
+///
+/// The body calls its sub-rules with `parse!(T, src)` and `skip_many!(T, src)`
+/// and the source with `peek!(src)` and `match_char!(src, ch)`. In each impl
+/// they expand to the awaited, `_sync` or `_send` version of the call.
+/// The items in front of the body only go into the `Parse` impl.
+/// Each trait is given with the token type of its impl, e.g. `Parse<K>`.
+macro_rules! combinator {
+    (
+        impl[$($generics:tt)*] $ty:ty;
+        Parse<$k:ty> where [$($parse_bounds:tt)*]
+        ParseSync<$k_sync:ty> where [$($sync_bounds:tt)*]
+        ParseSend<$k_send:ty> where [$($send_bounds:tt)*]
+        {
+            $($items:item)*
+        }
+        fn parse($src:ident) -> $ret:ty $body:block
+    ) => {
+        impl<$($generics)*> $crate::Parse<$k> for $ty where $($parse_bounds)* {
+            $($items)*
+            async fn parse<S: $crate::MatchToken<$k>>($src: &mut S) -> $ret {
+                #[allow(unused_imports)]
+                use $crate::combinator::asynchronous::*;
+                $body
+            }
+        }
+
+        impl<$($generics)*> $crate::ParseSync<$k_sync> for $ty where $($sync_bounds)* {
+            fn parse_sync<S: $crate::SourceSync + $crate::MatchToken<$k_sync>>($src: &mut S) -> $ret {
+                #[allow(unused_imports)]
+                use $crate::combinator::sync::*;
+                $body
+            }
+        }
+
+        impl<$($generics)*> $crate::ParseSend<$k_send> for $ty where $($send_bounds)* {
+            async fn parse_send<S: $crate::SourceSend + $crate::MatchToken<$k_send>>($src: &mut S) -> $ret {
+                #[allow(unused_imports)]
+                use $crate::combinator::send::*;
+                $body
+            }
+        }
+    };
+}
+
+pub(crate) use combinator;
+
+/// the calls of a `Parse` body
+pub(crate) mod asynchronous {
+    macro_rules! parse {
+        ($t:ty, $src:expr) => {
+            <$t as $crate::Parse<_>>::parse($src).await
+        };
+    }
+    macro_rules! skip_many {
+        ($t:ty, $src:expr) => {
+            <$t as $crate::Parse<_>>::skip_many($src).await
+        };
+    }
+    macro_rules! peek {
+        ($src:expr) => {
+            $crate::Source::peek($src).await
+        };
+    }
+    macro_rules! match_char {
+        ($src:expr, $ch:expr) => {
+            $crate::Source::match_char($src, $ch).await
+        };
+    }
+
+    pub(crate) use {match_char, parse, peek, skip_many};
+}
+
+/// the calls of a `ParseSync` body
+pub(crate) mod sync {
+    macro_rules! parse {
+        ($t:ty, $src:expr) => {
+            <$t as $crate::ParseSync<_>>::parse_sync($src)
+        };
+    }
+    macro_rules! skip_many {
+        ($t:ty, $src:expr) => {
+            <$t as $crate::ParseSync<_>>::skip_many_sync($src)
+        };
+    }
+    macro_rules! peek {
+        ($src:expr) => {
+            $crate::SourceSync::peek_sync($src)
+        };
+    }
+    macro_rules! match_char {
+        ($src:expr, $ch:expr) => {
+            $crate::SourceSync::match_char_sync($src, $ch)
+        };
+    }
+
+    pub(crate) use {match_char, parse, peek, skip_many};
+}
+
+/// the calls of a `ParseSend` body
+pub(crate) mod send {
+    macro_rules! parse {
+        ($t:ty, $src:expr) => {
+            <$t as $crate::ParseSend<_>>::parse_send($src).await
+        };
+    }
+    macro_rules! skip_many {
+        ($t:ty, $src:expr) => {
+            <$t as $crate::ParseSend<_>>::skip_many_send($src).await
+        };
+    }
+    macro_rules! peek {
+        ($src:expr) => {
+            $crate::SourceSend::peek_send($src).await
+        };
+    }
+    macro_rules! match_char {
+        ($src:expr, $ch:expr) => {
+            $crate::SourceSend::match_char_send($src, $ch).await
+        };
+    }
+
+    pub(crate) use {match_char, parse, peek, skip_many};
+}
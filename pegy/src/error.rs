@@ -1,4 +1,10 @@
+use core::ops::{Index, Range};
+
 use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{FileId, FileSpan};
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Span(pub usize, pub usize);
@@ -7,19 +13,109 @@ impl Span {
     pub const fn new(start: usize, end: usize) -> Self {
         Self(start, end)
     }
+
+    pub const fn len(&self) -> usize {
+        self.1.saturating_sub(self.0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the smallest span covering both spans
+    pub fn merge(self, other: Span) -> Span {
+        Span(self.0.min(other.0), self.1.max(other.1))
+    }
+
+    /// whether the position lies within the span, the end is exclusive
+    pub const fn contains(&self, pos: usize) -> bool {
+        self.0 <= pos && pos < self.1
+    }
+}
+
+impl From<Span> for Range<usize> {
+    fn from(span: Span) -> Self {
+        span.0..span.1
+    }
+}
+
+impl Index<Span> for str {
+    type Output = str;
+    fn index(&self, span: Span) -> &str {
+        &self[span.0..span.1]
+    }
+}
+
+/// A suggested edit of the input that fixes an error.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Suggestion {
+    /// the span to replace, empty for an insertion
+    pub span: Span,
+    /// the replacement text, empty for a deletion
+    pub replacement: Cow<'static, str>,
+}
+
+impl Suggestion {
+    pub fn new<S: Into<Cow<'static, str>>>(span: Span, replacement: S) -> Self {
+        Self {
+            span: span,
+            replacement: replacement.into(),
+        }
+    }
+}
+
+/// How serious a diagnostic is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// the parse failed or was recovered
+    #[default]
+    Error,
+    /// the parse succeeded, e.g. a deprecated syntax was used
+    Warning,
+}
+
+/// What caused an error.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorKind {
+    /// the input does not match the grammar
+    #[default]
+    Syntax,
+    /// reading the input failed, the parse stopped where the input was cut off
+    Io,
+    /// the input is not valid utf8 at the span
+    InvalidUtf8,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Error {
+    pub severity: Severity,
+    pub kind: ErrorKind,
     pub msg: Cow<'static, str>,
     pub span: Span,
+    /// the file of the source the error was produced from
+    pub file: Option<FileId>,
+    /// the set of terms that would have been accepted at `span`
+    pub expected: Vec<Cow<'static, str>>,
+    /// an optional error code, e.g. `E0042`
+    pub code: Option<Cow<'static, str>>,
+    /// an optional help text on how to fix the error
+    pub help: Option<Cow<'static, str>>,
+    /// an optional edit of the input that fixes the error
+    pub suggestion: Option<Suggestion>,
 }
 
 impl Default for Error {
     fn default() -> Self {
         Self {
+            severity: Severity::Error,
+            kind: ErrorKind::Syntax,
             msg: Cow::Borrowed("unknown"),
             span: Span(0, 0),
+            file: None,
+            expected: Vec::new(),
+            code: None,
+            help: None,
+            suggestion: None,
         }
     }
 }
@@ -27,8 +123,201 @@ impl Default for Error {
 impl Error {
     pub fn new<S: Into<Cow<'static, str>>>(span: Span, msg: S) -> Error {
         Error {
+            severity: Severity::Error,
+            kind: ErrorKind::Syntax,
             msg: msg.into(),
             span: span,
+            file: None,
+            expected: Vec::new(),
+            code: None,
+            help: None,
+            suggestion: None,
+        }
+    }
+
+    /// create a warning, a diagnostic that does not fail the parse
+    pub fn warning<S: Into<Cow<'static, str>>>(span: Span, msg: S) -> Error {
+        Error {
+            severity: Severity::Warning,
+            ..Error::new(span, msg)
+        }
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+
+    /// create an error for input that could not be read
+    pub fn io<S: Into<Cow<'static, str>>>(span: Span, msg: S) -> Error {
+        Error {
+            kind: ErrorKind::Io,
+            ..Error::new(span, msg)
+        }
+    }
+
+    pub fn is_io(&self) -> bool {
+        self.kind == ErrorKind::Io
+    }
+
+    /// create an error for input that is not valid utf8
+    pub fn invalid_utf8<S: Into<Cow<'static, str>>>(span: Span, msg: S) -> Error {
+        Error {
+            kind: ErrorKind::InvalidUtf8,
+            ..Error::new(span, msg)
+        }
+    }
+
+    /// replace the expected set and message with a single label
+    pub fn label<S: Into<Cow<'static, str>>>(mut self, label: S) -> Error {
+        self.expected.clear();
+        self.expected.push(label.into());
+        self.msg = Cow::Owned(expected_message(&self.expected));
+        return self;
+    }
+
+    /// set the error code if none is set yet
+    pub fn with_code<S: Into<Cow<'static, str>>>(mut self, code: S) -> Error {
+        if self.code.is_none() {
+            self.code = Some(code.into());
+        }
+        return self;
+    }
+
+    /// set the help text if none is set yet
+    pub fn with_help<S: Into<Cow<'static, str>>>(mut self, help: S) -> Error {
+        if self.help.is_none() {
+            self.help = Some(help.into());
+        }
+        return self;
+    }
+
+    /// attach a suggested fix
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Error {
+        self.suggestion = Some(suggestion);
+        return self;
+    }
+
+    /// set the file if none is set yet
+    pub fn with_file(mut self, file: Option<FileId>) -> Error {
+        if self.file.is_none() {
+            self.file = file;
+        }
+        return self;
+    }
+
+    /// the span together with its file, if the file is known
+    pub fn file_span(&self) -> Option<FileSpan> {
+        Some(FileSpan::new(self.file?, self.span))
+    }
+
+    /// add a term to the expected set
+    pub fn with_expected<S: Into<Cow<'static, str>>>(mut self, expected: S) -> Error {
+        let expected = expected.into();
+        if !self.expected.contains(&expected) {
+            self.expected.push(expected);
+        }
+        return self;
+    }
+
+    /// merge two errors, keeping the one that got further into the input.
+    ///
+    /// If both errors failed at the same position, their expected sets are
+    /// combined and the message is rebuilt from the combined set.
+    pub fn merge(self, other: Error) -> Error {
+        if other.span.0 > self.span.0 {
+            return other;
+        }
+        if other.span.0 < self.span.0 {
+            return self;
+        }
+
+        if other.expected.is_empty() {
+            return self;
+        }
+        if self.expected.is_empty() {
+            return other;
+        }
+
+        let mut merged = self;
+
+        if merged.file.is_none() {
+            merged.file = other.file;
+        }
+        if merged.code.is_none() {
+            merged.code = other.code;
         }
+        if merged.help.is_none() {
+            merged.help = other.help;
+        }
+        if merged.suggestion.is_none() {
+            merged.suggestion = other.suggestion;
+        }
+
+        for e in other.expected {
+            if !merged.expected.contains(&e) {
+                merged.expected.push(e);
+            }
+        }
+
+        merged.span.1 = merged.span.1.max(other.span.1);
+        merged.msg = Cow::Owned(expected_message(&merged.expected));
+
+        return merged;
+    }
+}
+
+/// format an expected set as `expected a, b or c`
+pub(crate) fn expected_message(expected: &[Cow<'static, str>]) -> String {
+    let mut msg = String::from("expected ");
+
+    for (i, e) in expected.iter().enumerate() {
+        if i != 0 {
+            if i + 1 == expected.len() {
+                msg.push_str(" or ");
+            } else {
+                msg.push_str(", ");
+            }
+        }
+        msg.push_str(e);
+    }
+
+    return msg;
+}
+
+/// parse a single quoted character or string literal as produced by `stringify!`.
+pub(crate) fn unquote(lit: &str) -> Option<String> {
+    let quote = lit.chars().next()?;
+    if quote != '\'' && quote != '"' {
+        return None;
+    }
+
+    let mut buf = String::new();
+    let mut chars = lit[1..].chars();
+
+    loop {
+        match chars.next()? {
+            '\\' => buf.push(match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                c => c,
+            }),
+            c if c == quote => break,
+            c => buf.push(c),
+        }
+    }
+
+    // the whole label must be a single literal
+    if chars.next().is_some() || buf.is_empty() {
+        return None;
+    }
+
+    return Some(buf);
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.msg)
     }
 }
@@ -0,0 +1,81 @@
+use crate::{Error, MatchToken, Parse, Source, Span};
+
+/// Searches the input for matches of a rule, one after another.
+pub(crate) struct Finder<S: Source> {
+    src: S,
+    is_done: bool,
+}
+
+impl<S: Source> Finder<S> {
+    pub fn new(src: S) -> Self {
+        Self {
+            src: src,
+            is_done: false,
+        }
+    }
+
+    /// find the next match, `None` at the end of input or after an error
+    pub async fn next<T: Parse<K>, K>(&mut self) -> Option<Result<(T::Output, Span), Error>>
+    where
+        S: MatchToken<K>,
+    {
+        if self.is_done {
+            return None;
+        }
+
+        loop {
+            // skip the characters no match can start with in a single scan
+            self.src.take_while(|c| !T::may_start_with(c)).await;
+
+            let start = self.src.current_position();
+            // a match never starts before the current position again
+            self.src.commit(start);
+
+            let re = T::parse(&mut self.src).await;
+
+            if let Some(e) = crate::input_error(&mut self.src) {
+                self.is_done = true;
+                return Some(Err(e));
+            }
+
+            match re {
+                Ok(v) => {
+                    let end = self.src.current_position();
+
+                    // the next search starts after an empty match
+                    if end == start {
+                        match self.src.peek().await {
+                            Some(c) => self.src.set_position(start + c.length),
+                            None => self.is_done = true,
+                        }
+                    }
+
+                    return Some(Ok((v, Span::new(start, end))));
+                }
+                Err(_) => {
+                    self.src.set_position(start);
+                    match self.src.peek().await {
+                        Some(c) => self.src.set_position(start + c.length),
+                        None => {
+                            self.is_done = true;
+                            return match crate::input_error(&mut self.src) {
+                                Some(e) => Some(Err(e)),
+                                None => None,
+                            };
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+pub(crate) fn find_iter<T: Parse<K>, K, S: MatchToken<K>>(
+    src: S,
+) -> impl futures::Stream<Item = Result<(T::Output, Span), Error>> {
+    futures::stream::unfold(Finder::new(src), |mut finder| async move {
+        let item = finder.next::<T, K>().await?;
+        Some((item, finder))
+    })
+}
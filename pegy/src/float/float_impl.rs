@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::Source;
 
 #[allow(invalid_value)]
@@ -31,18 +33,7 @@ pub async fn parse<F: fast_float::FastFloat, S: Source>(src: &mut S) -> Option<F
             stack_pos += 1;
         };
 
-        while let Some(ch) = src.match_char_range('0'..='9').await {
-            if stack_pos == 1024 {
-                heap_buffer.push(ch as u8);
-            } else {
-                stack_buffer[stack_pos] = ch as u8;
-                stack_pos += 1;
-
-                if stack_pos == 1024 {
-                    heap_buffer = stack_buffer.to_vec();
-                }
-            }
-        }
+        push_digits(src, &mut stack_buffer, &mut stack_pos, &mut heap_buffer).await;
     } else if src.match_char('.').await {
         dot = true;
 
@@ -95,18 +86,7 @@ pub async fn parse<F: fast_float::FastFloat, S: Source>(src: &mut S) -> Option<F
     };
 
     if dot {
-        while let Some(ch) = src.match_char_range('0'..='9').await {
-            if stack_pos == 1024 {
-                heap_buffer.push(ch as u8);
-            } else {
-                stack_buffer[stack_pos] = ch as u8;
-                stack_pos += 1;
-
-                if stack_pos == 1024 {
-                    heap_buffer = stack_buffer.to_vec();
-                }
-            }
-        }
+        push_digits(src, &mut stack_buffer, &mut stack_pos, &mut heap_buffer).await;
     };
 
     let e_pos = src.current_position();
@@ -161,21 +141,7 @@ pub async fn parse<F: fast_float::FastFloat, S: Source>(src: &mut S) -> Option<F
             };
 
             e_len += 1;
-
-            while let Some(ch) = src.match_char_range('0'..='9').await {
-                if stack_pos == 1024 {
-                    heap_buffer.push(ch as u8);
-                } else {
-                    stack_buffer[stack_pos] = ch as u8;
-                    stack_pos += 1;
-
-                    if stack_pos == 1024 {
-                        heap_buffer = stack_buffer.to_vec();
-                    }
-                };
-
-                e_len += 1;
-            }
+            e_len += push_digits(src, &mut stack_buffer, &mut stack_pos, &mut heap_buffer).await;
         } else {
             // no an exponent
             src.set_position(e_pos);
@@ -195,6 +161,57 @@ pub async fn parse<F: fast_float::FastFloat, S: Source>(src: &mut S) -> Option<F
     }
 }
 
+/// append a byte to the number buffer, it moves to the heap once the stack buffer is full
+#[inline]
+fn push_byte(
+    stack_buffer: &mut [u8; 1024],
+    stack_pos: &mut usize,
+    heap_buffer: &mut Vec<u8>,
+    b: u8,
+) {
+    if *stack_pos == 1024 {
+        heap_buffer.push(b);
+    } else {
+        stack_buffer[*stack_pos] = b;
+        *stack_pos += 1;
+
+        if *stack_pos == 1024 {
+            *heap_buffer = stack_buffer.to_vec();
+        }
+    }
+}
+
+/// consume a run of digits into the number buffer, return the number of digits
+#[inline]
+async fn push_digits<S: Source>(
+    src: &mut S,
+    stack_buffer: &mut [u8; 1024],
+    stack_pos: &mut usize,
+    heap_buffer: &mut Vec<u8>,
+) -> usize {
+    let run = src.take_digits().await;
+
+    match src.slice(run) {
+        Some(digits) => {
+            for b in digits.bytes() {
+                push_byte(stack_buffer, stack_pos, heap_buffer, b);
+            }
+            digits.len()
+        }
+        None => {
+            // the source does not retain its input, read the digits again
+            src.set_position(run.0);
+
+            let mut n = 0;
+            while let Some(ch) = src.match_char_range('0'..='9').await {
+                push_byte(stack_buffer, stack_pos, heap_buffer, ch as u8);
+                n += 1;
+            }
+            n
+        }
+    }
+}
+
 /*
 use super::{binary::compute_float, float::Float};
 
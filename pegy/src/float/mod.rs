@@ -7,3 +7,8 @@ mod float_impl;
 //mod table;
 
 pub use float_impl::parse;
+
+/// the characters a float can start with, including `inf` and `nan`
+pub fn may_start_with(c: char) -> bool {
+    c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'i' | 'I' | 'n' | 'N')
+}
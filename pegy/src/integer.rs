@@ -1,7 +1,8 @@
 use core::fmt::{Debug, Display};
 use core::ops::{Add, BitAnd, Div, Mul, Shl, Sub};
 
-use crate::{Error, Parse, Source, Span};
+use crate::send::parse_through_source;
+use crate::{Error, Parse, ParseSend, ParseSync, Source, Span};
 
 pub trait Integer:
     Sized
@@ -32,7 +33,7 @@ pub trait Integer:
     fn checked_add(self, rhs: Self) -> Option<Self>;
 }
 
-impl<I: Integer> Parse for I {
+impl<K, I: Integer> Parse<K> for I {
     type Output = I;
     async fn parse<S: Source>(src: &mut S) -> Result<Self::Output, Error> {
         let start = src.current_position();
@@ -140,20 +141,11 @@ impl<I: Integer> Parse for I {
             while src.match_char('0').await {}
         };
 
-        if let Some(c) = src.match_char_range('0'..='9').await {
+        // the run of digits is scanned at once and accumulated afterwards
+        let run = src.take_digits().await;
+
+        if !run.is_empty() {
             has_digt = true;
-            i = I::from_u8(c as u8 - '0' as u8);
-
-            for _ in 0..I::MAX_SAFE_DIGITS - 1 {
-                if let Some(c) = src.match_char_range('0'..='9').await {
-                    let digit = c as u8 - '0' as u8;
-                    i = i
-                        .wrapping_mul(I::from_u8(10))
-                        .wrapping_add(I::from_u8(digit));
-                } else {
-                    return Ok(i);
-                }
-            }
         }
 
         if !has_digt {
@@ -162,21 +154,35 @@ impl<I: Integer> Parse for I {
             return Err(Error::new(Span::new(start, end), "error parsing integer"));
         }
 
-        while let Some(c) = src.match_char_range('0'..='9').await {
-            if let Some(m) = i.checked_mul(I::from_u8(10)) {
-                if let Some(n) = m.checked_add(I::from_u8(c as u8 - '0' as u8)) {
-                    i = n;
-                    continue;
+        let value = match src.slice(run) {
+            Some(s) => s
+                .bytes()
+                .enumerate()
+                .try_fold(i, |i, (n, b)| push_digit(i, n, b - b'0')),
+            None => {
+                // the source does not retain its input, read the digits again
+                src.set_position(run.0);
+
+                let mut value = Some(i);
+                let mut n = 0;
+                while let Some(c) = src.match_char_range('0'..='9').await {
+                    value = value.and_then(|i| push_digit(i, n, c as u8 - b'0'));
+                    n += 1;
                 }
-            };
+                value
+            }
+        };
 
-            let end = src.current_position();
-            src.set_position(start);
-            return Err(Error::new(
-                Span::new(start, end),
-                "overflow while parsing integer",
-            ));
-        }
+        i = match value {
+            Some(i) => i,
+            None => {
+                src.set_position(start);
+                return Err(Error::new(
+                    Span::new(start, run.1),
+                    "overflow while parsing integer",
+                ));
+            }
+        };
 
         if is_neg {
             return Ok(I::from_u8(0) - i);
@@ -184,6 +190,28 @@ impl<I: Integer> Parse for I {
 
         return Ok(i);
     }
+    fn may_start_with(c: char) -> bool {
+        c.is_ascii_digit() || (c == '-' && I::MIN < I::from_u8(0))
+    }
+}
+
+impl<K, I: Integer> ParseSync<K> for I {}
+
+impl<K, I: Integer + Send> ParseSend<K> for I {
+    parse_through_source!();
+}
+
+/// append a decimal digit, the first `MAX_SAFE_DIGITS` digits cannot overflow
+#[inline]
+fn push_digit<I: Integer>(i: I, n: usize, digit: u8) -> Option<I> {
+    if n < I::MAX_SAFE_DIGITS {
+        return Some(
+            i.wrapping_mul(I::from_u8(10))
+                .wrapping_add(I::from_u8(digit)),
+        );
+    }
+    i.checked_mul(I::from_u8(10))?
+        .checked_add(I::from_u8(digit))
 }
 
 macro_rules! impl_int {
@@ -1,36 +1,341 @@
 #![cfg_attr(feature = "simd", feature(portable_simd))]
+#![cfg_attr(not(feature = "futures"), no_std)]
 
 extern crate alloc;
 
+use alloc::vec::Vec;
+
+mod combinator;
 mod error;
+mod find;
 mod float;
 mod integer;
 mod parse;
+mod partial;
 pub mod pratt;
+mod repair;
+mod report;
+mod scan;
+mod send;
 mod source;
+mod source_map;
+#[cfg(feature = "futures")]
+mod stream;
+mod suggest;
+mod sync;
+mod token;
+#[cfg(feature = "proc-macro2")]
+pub mod token_tree;
 pub mod util;
 
 #[cfg(feature = "futures")]
 pub use futures;
 
-pub use pegy_derive::Parse;
+pub use pegy_derive::{Parse, ParseSend, ParseSync};
 
-pub use error::{Error, Span};
+pub use error::{Error, ErrorKind, Severity, Span, Suggestion};
 pub use parse::Parse;
-pub use source::{AsyncStrSource, Character, IntoSource, Source, StrSource};
+pub use partial::{Partial, PartialSource};
+pub use report::Report;
+pub use send::{ParseSend, SourceSend};
+#[cfg(feature = "futures")]
+pub use source::AsyncStrSource;
+pub use source::{
+    ByteSource, Character, IntoSource, RecordingSource, Source, StrSource, Utf8Policy,
+};
+pub use source_map::{FileId, FileSpan, LineCol, LineIndex, Location, SourceFile, SourceMap};
+pub use sync::{ParseSync, SourceSync};
+pub use token::{MatchToken, RecordedToken, Token, TokenSource};
 
+#[cfg(feature = "futures")]
 pub mod io {
     pub use crate::source::AsyncStreamRead;
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
 
-pub async fn parse<T: Parse, S: IntoSource>(src: S) -> Result<T::Output> {
+/// parse a `T` at the start of the input, the input after it is ignored.
+///
+/// Use `parse_complete` to fail on trailing input.
+pub async fn parse<T: Parse<K>, K>(src: impl IntoSource<K>) -> Result<T::Output> {
+    parse_prefix::<T, K>(src).await.map(|(v, _)| v)
+}
+
+/// parse a `T` at the start of the input,
+/// return it together with the position it ends at.
+pub async fn parse_prefix<T: Parse<K>, K>(src: impl IntoSource<K>) -> Result<(T::Output, usize)> {
+    let mut src = src.into();
+    let re = T::parse(&mut src).await;
+
+    if let Some(e) = input_error(&mut src) {
+        return Err(e);
+    }
+
+    match re {
+        Ok(v) => Ok((v, src.current_position())),
+        Err(e) => Err(finish_error(&mut src, e).await),
+    }
+}
+
+/// parse a `T` at the start of an input that never pends, without futures.
+///
+/// The rules must implement `ParseSync`, e.g. by `#[derive(ParseSync)]`.
+pub fn parse_sync<T: ParseSync<K>, K>(
+    src: impl IntoSource<K, Source: SourceSync>,
+) -> Result<T::Output> {
+    let mut src = src.into();
+    let re = T::parse_sync(&mut src);
+
+    if let Some(e) = input_error(&mut src) {
+        return Err(e);
+    }
+
+    match re {
+        Ok(v) => Ok(v),
+        Err(e) => Err(sync::poll_ready(finish_error(&mut src, e))),
+    }
+}
+
+/// parse a `T` at the start of the input in a future that is `Send`,
+/// so that it can run in a task of a multi-threaded runtime, e.g. `tokio::spawn`.
+///
+/// The rules must implement `ParseSend`, e.g. by `#[derive(ParseSend)]`.
+pub fn parse_send<T: ParseSend<K>, K>(
+    src: impl IntoSource<K, Source: SourceSend>,
+) -> impl core::future::Future<Output = Result<T::Output>> + Send
+where
+    T::Output: Send,
+{
+    let mut src = src.into();
+
+    async move {
+        let re = T::parse_send(&mut src).await;
+
+        if let Some(e) = input_error(&mut src) {
+            return Err(e);
+        }
+
+        match re {
+            Ok(v) => Ok(v),
+            Err(e) => Err(finish_error(&mut send::SendSource(&mut src), e).await),
+        }
+    }
+}
+
+/// parse a `T` that spans the whole input.
+///
+/// Fails with "unexpected trailing input" at the first character
+/// after `T` if there is any.
+pub async fn parse_complete<T: Parse<K>, K>(src: impl IntoSource<K>) -> Result<T::Output> {
     let mut src = src.into();
-    T::parse(&mut src).await
+    let re = T::parse(&mut src).await;
+
+    let trailing = match &re {
+        Ok(_) => src.peek().await,
+        Err(_) => None,
+    };
+
+    if let Some(e) = input_error(&mut src) {
+        return Err(e);
+    }
+
+    match (re, trailing) {
+        (Ok(v), None) => Ok(v),
+        (Ok(_), Some(c)) => {
+            let pos = src.current_position();
+            let e = Error::new(Span::new(pos, pos + c.length), "unexpected trailing input")
+                .with_expected("EOF");
+            Err(e.with_file(src.file()))
+        }
+        (Err(e), _) => Err(finish_error(&mut src, e).await),
+    }
+}
+
+/// attach the file and a suggestion to an error returned to the caller
+async fn finish_error<S: Source>(src: &mut S, error: Error) -> Error {
+    let error = error.with_file(src.file());
+    suggest::did_you_mean(src, error).await
+}
+
+/// the error that cut the input off, it replaces the result of the parse
+fn input_error<S: Source>(src: &mut S) -> Option<Error> {
+    let file = src.file();
+    Some(src.take_input_error()?.with_file(file))
+}
+
+pub fn parse_blocking<T: Parse<K>, K>(src: impl IntoSource<K>) -> Result<T::Output> {
+    sync::block_on(parse::<T, K>(src))
+}
+
+pub fn parse_prefix_blocking<T: Parse<K>, K>(
+    src: impl IntoSource<K>,
+) -> Result<(T::Output, usize)> {
+    sync::block_on(parse_prefix::<T, K>(src))
+}
+
+pub fn parse_complete_blocking<T: Parse<K>, K>(src: impl IntoSource<K>) -> Result<T::Output> {
+    sync::block_on(parse_complete::<T, K>(src))
+}
+
+/// parse and collect the warnings emitted by rules.
+///
+/// Warnings emitted on paths that were backtracked are not returned.
+pub async fn parse_with_warnings<T: Parse<K>, K: RecordedToken>(
+    src: impl IntoSource<K>,
+) -> Result<(T::Output, Vec<Error>)> {
+    let mut src = RecordingSource::without_recovery(src.into());
+    let re = K::parse_recording::<T, _>(&mut src).await;
+
+    let (mut src, warnings) = src.into_parts();
+
+    if let Some(e) = input_error(&mut src) {
+        return Err(e);
+    }
+
+    let warnings = warnings
+        .into_iter()
+        .map(|w| w.with_file(src.file()))
+        .collect();
+
+    match re {
+        Ok(v) => Ok((v, warnings)),
+        Err(e) => Err(finish_error(&mut src, e).await),
+    }
+}
+
+pub fn parse_with_warnings_blocking<T: Parse<K>, K: RecordedToken>(
+    src: impl IntoSource<K>,
+) -> Result<(T::Output, Vec<Error>)> {
+    sync::block_on(parse_with_warnings::<T, K>(src))
+}
+
+/// parse with error recovery enabled.
+///
+/// Errors that rules recovered from, e.g. by `recover(term, until = ...)`,
+/// are collected instead of aborting the parse, together with warnings.
+/// The value is `None` if the parse failed regardless, its error is then
+/// the last one returned.
+pub async fn parse_with_recovery<T: Parse<K>, K: RecordedToken>(
+    src: impl IntoSource<K>,
+) -> (Option<T::Output>, Vec<Error>) {
+    let mut src = RecordingSource::new(src.into());
+    let re = K::parse_recording::<T, _>(&mut src).await;
+
+    let (mut src, recorded) = src.into_parts();
+
+    let mut errors = Vec::with_capacity(recorded.len() + 1);
+    for e in recorded {
+        errors.push(finish_error(&mut src, e).await);
+    }
+
+    if let Some(e) = input_error(&mut src) {
+        errors.push(e);
+        return (None, errors);
+    }
+
+    match re {
+        Ok(v) => (Some(v), errors),
+        Err(e) => {
+            errors.push(finish_error(&mut src, e).await);
+            (None, errors)
+        }
+    }
+}
+
+pub fn parse_with_recovery_blocking<T: Parse<K>, K: RecordedToken>(
+    src: impl IntoSource<K>,
+) -> (Option<T::Output>, Vec<Error>) {
+    sync::block_on(parse_with_recovery::<T, K>(src))
+}
+
+/// parse one `T` after another until the end of input.
+///
+/// The input of each item is committed once it is parsed, so an
+/// `AsyncStrSource` only holds the item being parsed in memory.
+/// The stream ends after the first error.
+#[cfg(feature = "futures")]
+pub fn parse_stream<T: Parse<K>, K>(
+    src: impl IntoSource<K>,
+) -> impl futures::Stream<Item = Result<T::Output>> {
+    stream::parse_stream::<T, (), K>(src)
 }
 
+/// parse one `T` after another until the end of input,
+/// with a `Sep` between every two items, e.g. a newline.
+///
+/// A trailing separator is accepted.
 #[cfg(feature = "futures")]
-pub fn parse_blocking<T: Parse, S: IntoSource>(src: S) -> Result<T::Output> {
-    futures::executor::block_on(parse::<T, S>(src))
+pub fn parse_stream_separated<T: Parse<K>, Sep: Parse<K>, K>(
+    src: impl IntoSource<K>,
+) -> impl futures::Stream<Item = Result<T::Output>> {
+    stream::parse_stream::<T, Sep, K>(src)
+}
+
+/// find the first match of `T` in the input, together with its span.
+///
+/// Positions where `T` fails are skipped one character at a time,
+/// characters that `T::may_start_with` rules out are skipped in a single scan.
+pub async fn find<T: Parse<K>, K>(src: impl IntoSource<K>) -> Result<Option<(T::Output, Span)>> {
+    find::Finder::new(src.into())
+        .next::<T, K>()
+        .await
+        .transpose()
+}
+
+pub fn find_blocking<T: Parse<K>, K>(src: impl IntoSource<K>) -> Result<Option<(T::Output, Span)>> {
+    sync::block_on(find::<T, K>(src))
+}
+
+/// find the matches of `T` one after another, like `regex::Regex::find_iter`.
+///
+/// Matches do not overlap, the search continues after the end of a match.
+/// The input before a match is committed once it is searched.
+#[cfg(feature = "futures")]
+pub fn find_iter<T: Parse<K>, K>(
+    src: impl IntoSource<K>,
+) -> impl futures::Stream<Item = Result<(T::Output, Span)>> {
+    find::find_iter::<T, K, _>(src.into())
+}
+
+#[cfg(feature = "futures")]
+pub fn find_iter_blocking<T: Parse<K>, K>(
+    src: impl IntoSource<K>,
+) -> impl Iterator<Item = Result<(T::Output, Span)>> {
+    futures::executor::block_on_stream(alloc::boxed::Box::pin(find_iter::<T, K>(src)))
+}
+
+/// parse the input fed to `src` so far.
+///
+/// If the parse reaches the end of the input before `PartialSource::finish`
+/// is called, the result is `Partial::Incomplete` and the position is reset,
+/// so that the value is parsed again once more input is fed.
+/// The input of a completed value is committed, parsing values one at a
+/// time therefore never parses a completed value again.
+pub async fn parse_partial<T: Parse>(src: &mut PartialSource) -> Partial<T::Output> {
+    partial::parse_partial::<T>(src).await
+}
+
+pub fn parse_partial_blocking<T: Parse>(src: &mut PartialSource) -> Partial<T::Output> {
+    sync::block_on(parse_partial::<T>(src))
+}
+
+/// parse with error recovery and single token repairs.
+///
+/// When the parse fails, the input is repaired at the failure position by
+/// either inserting one of the expected literals or deleting one character.
+/// The repair that lets the parse get furthest is applied and parsing is
+/// retried, up to `max_repairs` times. Each repair is reported as an error
+/// with a `Suggestion`, all spans refer to the original text.
+pub async fn parse_with_repair<T: Parse>(
+    text: &str,
+    max_repairs: usize,
+) -> (Option<T::Output>, Vec<Error>) {
+    repair::parse_with_repair::<T>(text, max_repairs).await
+}
+
+pub fn parse_with_repair_blocking<T: Parse>(
+    text: &str,
+    max_repairs: usize,
+) -> (Option<T::Output>, Vec<Error>) {
+    sync::block_on(parse_with_repair::<T>(text, max_repairs))
 }
@@ -1,13 +1,33 @@
-use crate::{error::Error, Source};
+use crate::combinator::combinator;
+use crate::send::parse_through_source;
+use crate::{error::Error, MatchToken, ParseSend, ParseSync, Source};
 
+/// A rule of a grammar.
+///
+/// `K` is the token type of the sources the rule parses, see `MatchToken`. Rules over
+/// text keep the default and rules that only call other rules implement `Parse<K>` for every `K`.
 #[allow(async_fn_in_trait)]
-pub trait Parse {
+pub trait Parse<K = core::convert::Infallible> {
     type Output: Default;
     /// function `parse` should not consume any character on failure
-    async fn parse<S: Source>(src: &mut S) -> Result<Self::Output, Error>;
+    async fn parse<S: MatchToken<K>>(src: &mut S) -> Result<Self::Output, Error>;
+    /// consume as many repetitions of the rule as possible without producing values,
+    /// return the number of repetitions or `None` if the rule cannot skip in bulk.
+    ///
+    /// Single character rules implement this to scan the input at once.
+    async fn skip_many<S: MatchToken<K>>(_src: &mut S) -> Option<usize> {
+        None
+    }
+    /// return false if the rule cannot match where the next character is `c`.
+    ///
+    /// Searching uses it to skip positions without parsing, a rule that
+    /// may match without consuming input returns true for every character.
+    fn may_start_with(_c: char) -> bool {
+        true
+    }
 }
 
-impl Parse for f64 {
+impl<K> Parse<K> for f64 {
     type Output = f64;
 
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, crate::Error> {
@@ -25,9 +45,12 @@ impl Parse for f64 {
             }
         }
     }
+    fn may_start_with(c: char) -> bool {
+        crate::float::may_start_with(c)
+    }
 }
 
-impl Parse for f32 {
+impl<K> Parse<K> for f32 {
     type Output = f32;
     async fn parse<S: Source>(src: &mut S) -> Result<Self::Output, Error> {
         let start = src.current_position();
@@ -44,23 +67,47 @@ impl Parse for f32 {
             }
         }
     }
+    fn may_start_with(c: char) -> bool {
+        crate::float::may_start_with(c)
+    }
 }
 
-impl<T: Parse> Parse for Option<T> {
-    type Output = Option<T::Output>;
+impl<K> ParseSync<K> for f64 {}
+impl<K> ParseSync<K> for f32 {}
 
-    #[inline]
-    async fn parse<S: Source>(src: &mut S) -> Result<Self::Output, Error> {
-        if let Ok(v) = T::parse(src).await {
+impl<K> ParseSend<K> for f64 {
+    parse_through_source!();
+}
+
+impl<K> ParseSend<K> for f32 {
+    parse_through_source!();
+}
+
+combinator! {
+    impl[K, T] Option<T>;
+    Parse<K> where [T: Parse<K>]
+    ParseSync<K> where [T: ParseSync<K>]
+    ParseSend<K> where [T: ParseSend<K>]
+    {
+        type Output = Option<T::Output>;
+    }
+    fn parse(src) -> Result<Self::Output, Error> {
+        if let Ok(v) = parse!(T, src) {
             return Ok(Some(v));
         }
         return Ok(None);
     }
 }
 
-impl Parse for () {
+impl<K> Parse<K> for () {
     type Output = ();
     async fn parse<S: Source>(_src: &mut S) -> Result<Self::Output, Error> {
         return Ok(());
     }
 }
+
+impl<K> ParseSync<K> for () {}
+
+impl<K> ParseSend<K> for () {
+    parse_through_source!();
+}
@@ -0,0 +1,265 @@
+use alloc::vec::Vec;
+
+use crate::send::forward_source_send;
+use crate::source::Character;
+use crate::{Error, FileId, Parse, Source, SourceSend, Span};
+
+/// The result of parsing the input available so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Partial<T> {
+    /// the value was parsed, more input would not change it
+    Complete(T),
+    /// the parse reached the end of the input fed so far,
+    /// feed more and parse again.
+    Incomplete {
+        /// the least number of bytes that may change the outcome
+        needed_hint: usize,
+    },
+    /// the input is invalid, more input would not change that
+    Error(Error),
+}
+
+/// A source that is fed input as it arrives, e.g. lines of a REPL
+/// or packets of a network protocol.
+///
+/// Running out of input is reported by `parse_partial` as
+/// `Partial::Incomplete` instead of an error until `finish` is called.
+/// The input of completed values is dropped, positions stay absolute.
+///
+/// ```
+/// use pegy::{Partial, PartialSource};
+///
+/// let mut src = PartialSource::new();
+/// src.feed(b"12");
+/// assert_eq!(
+///     pegy::parse_partial_blocking::<u32>(&mut src),
+///     Partial::Incomplete { needed_hint: 1 }
+/// );
+///
+/// src.feed(b"3;");
+/// assert_eq!(pegy::parse_partial_blocking::<u32>(&mut src), Partial::Complete(123));
+/// ```
+#[derive(Debug, Default)]
+pub struct PartialSource {
+    buffer: Vec<u8>,
+    /// the position of the first byte in `buffer`
+    base: usize,
+    pos: usize,
+    /// the length of the valid utf8 at the start of `buffer`
+    valid: usize,
+    is_final: bool,
+    /// the first sequence that is not valid utf8
+    utf8_error: Option<Span>,
+    /// the parse reached `utf8_error`
+    hit_invalid: bool,
+    /// the smallest amount of input needed by a term that reached the end
+    needed: Option<usize>,
+    file: Option<FileId>,
+}
+
+impl PartialSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// bind the source to a file, errors produced from it carry the file id
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// append input, a character may be split across calls
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+
+        if self.utf8_error.is_some() {
+            return;
+        }
+
+        match core::str::from_utf8(&self.buffer[self.valid..]) {
+            Ok(_) => self.valid = self.buffer.len(),
+            Err(e) => {
+                self.valid += e.valid_up_to();
+
+                if let Some(len) = e.error_len() {
+                    let start = self.base + self.valid;
+                    self.utf8_error = Some(Span::new(start, start + len));
+                }
+            }
+        }
+    }
+
+    /// mark the end of the input, running out of input is then an error
+    pub fn finish(&mut self) {
+        self.is_final = true;
+
+        if self.utf8_error.is_none() && self.valid < self.buffer.len() {
+            let start = self.base + self.valid;
+            self.utf8_error = Some(Span::new(start, self.base + self.buffer.len()));
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_final
+    }
+
+    /// the position of the first byte still held
+    pub fn buffer_start(&self) -> usize {
+        self.base
+    }
+
+    /// the valid input after the position
+    fn rest(&self) -> &str {
+        let start = (self.pos - self.base).min(self.valid);
+        // SAFETY: `valid` is the end of the checked utf8 and positions are char boundaries
+        unsafe { core::str::from_utf8_unchecked(&self.buffer[start..self.valid]) }
+    }
+
+    /// note that a term needed `needed` more bytes than available
+    fn reached_end(&mut self, needed: usize) {
+        if self.utf8_error.is_some() {
+            self.hit_invalid = true;
+            return;
+        }
+        if self.is_final {
+            return;
+        }
+
+        // the leading bytes of a split character are already there
+        let needed = match self.buffer.get(self.valid) {
+            Some(b) => {
+                let have = self.buffer.len() - self.valid;
+                let width = b.leading_ones() as usize;
+                needed.max(width).saturating_sub(have).max(1)
+            }
+            None => needed,
+        };
+
+        self.needed = Some(match self.needed {
+            Some(n) => n.min(needed),
+            None => needed,
+        });
+    }
+}
+
+impl Source for PartialSource {
+    #[inline]
+    fn current_position(&self) -> usize {
+        self.pos
+    }
+    #[inline]
+    fn set_position(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+    fn slice(&self, span: Span) -> Option<&str> {
+        let start = span.0.checked_sub(self.base)?;
+        let end = span.1.checked_sub(self.base)?;
+        if end > self.valid {
+            return None;
+        }
+        core::str::from_utf8(self.buffer.get(start..end)?).ok()
+    }
+    fn file(&self) -> Option<FileId> {
+        self.file
+    }
+    fn take_input_error(&mut self) -> Option<Error> {
+        if !self.hit_invalid {
+            return None;
+        }
+        self.hit_invalid = false;
+
+        let span = self.utf8_error?;
+        return Some(Error::invalid_utf8(
+            span,
+            alloc::format!("invalid utf-8 sequence at byte {}", span.0),
+        ));
+    }
+    fn commit(&mut self, pos: usize) {
+        let drop = pos.min(self.base + self.valid).saturating_sub(self.base);
+
+        // the prefix is only dropped once it is as long as the rest,
+        // so every byte is moved at most once on average
+        if drop == 0 || drop < self.buffer.len() - drop {
+            return;
+        }
+
+        self.buffer.drain(..drop);
+        self.base += drop;
+        self.valid -= drop;
+    }
+
+    async fn peek(&mut self) -> Option<Character> {
+        if self.pos < self.base {
+            return None;
+        }
+
+        match self.rest().chars().next() {
+            Some(ch) => Some(Character {
+                ch: ch,
+                length: ch.len_utf8(),
+            }),
+            None => {
+                self.reached_end(1);
+                None
+            }
+        }
+    }
+
+    async fn match_str(&mut self, string: &str) -> bool {
+        if self.pos < self.base {
+            return false;
+        }
+
+        let rest = self.rest().as_bytes();
+
+        if rest.len() >= string.len() {
+            if rest.starts_with(string.as_bytes()) {
+                self.pos += string.len();
+                return true;
+            }
+            return false;
+        }
+
+        // the input so far is a prefix of the string
+        if string.as_bytes().starts_with(rest) {
+            let needed = string.len() - rest.len();
+            self.reached_end(needed);
+        }
+        return false;
+    }
+}
+
+impl SourceSend for PartialSource {
+    forward_source_send!();
+}
+
+pub(crate) async fn parse_partial<T: Parse>(src: &mut PartialSource) -> Partial<T::Output> {
+    let start = src.current_position();
+    src.needed = None;
+
+    let re = T::parse(src).await;
+
+    if let Some(e) = crate::input_error(src) {
+        return Partial::Error(e);
+    }
+
+    // a parse that reached the end may go differently with more input
+    if let Some(needed) = src.needed.take() {
+        src.set_position(start);
+        return Partial::Incomplete {
+            needed_hint: needed,
+        };
+    }
+
+    match re {
+        Ok(v) => {
+            src.commit(src.current_position());
+            Partial::Complete(v)
+        }
+        Err(e) => {
+            let e = crate::finish_error(src, e).await;
+            src.set_position(start);
+            Partial::Error(e)
+        }
+    }
+}
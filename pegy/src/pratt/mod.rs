@@ -1,6 +1,7 @@
 use core::marker::PhantomData;
 
 use alloc::boxed::Box;
+use alloc::string::String;
 use alloc::vec::Vec;
 
 use crate::{Error, Source, Span};
@@ -0,0 +1,240 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::{unquote, Suggestion};
+use crate::{Error, Parse, Span};
+
+/// A part of the repaired input.
+#[derive(Clone)]
+enum Segment {
+    /// a range of the original input
+    Original(usize, usize),
+    /// text inserted by a repair
+    Inserted(String),
+}
+
+/// The original input with the repairs applied.
+#[derive(Clone)]
+struct Document<'a> {
+    text: &'a str,
+    segments: Vec<Segment>,
+}
+
+impl<'a> Document<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text: text,
+            segments: alloc::vec![Segment::Original(0, text.len())],
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut buf = String::with_capacity(self.text.len());
+        for s in &self.segments {
+            match s {
+                Segment::Original(start, end) => buf.push_str(&self.text[*start..*end]),
+                Segment::Inserted(text) => buf.push_str(text),
+            }
+        }
+        return buf;
+    }
+
+    /// map a position in the repaired input back to the original input
+    fn to_original(&self, pos: usize) -> usize {
+        let mut offset = 0;
+        let mut original = 0;
+
+        for s in &self.segments {
+            match s {
+                Segment::Original(start, end) => {
+                    if pos <= offset + (end - start) {
+                        return start + (pos - offset);
+                    }
+                    offset += end - start;
+                    original = *end;
+                }
+                Segment::Inserted(text) => {
+                    if pos < offset + text.len() {
+                        return original;
+                    }
+                    offset += text.len();
+                }
+            }
+        }
+
+        return original;
+    }
+
+    fn map_span(&self, span: Span) -> Span {
+        Span::new(self.to_original(span.0), self.to_original(span.1))
+    }
+
+    /// split the segments at a position of the repaired input,
+    /// returning the index of the segment that starts there.
+    fn split(&mut self, pos: usize) -> usize {
+        let mut offset = 0;
+
+        for i in 0..self.segments.len() {
+            if offset == pos {
+                return i;
+            }
+
+            let len = match &self.segments[i] {
+                Segment::Original(start, end) => end - start,
+                Segment::Inserted(text) => text.len(),
+            };
+
+            if pos < offset + len {
+                if let Segment::Original(start, end) = self.segments[i] {
+                    let mid = start + (pos - offset);
+                    self.segments[i] = Segment::Original(start, mid);
+                    self.segments.insert(i + 1, Segment::Original(mid, end));
+                    return i + 1;
+                }
+                // positions inside inserted text are never split
+                return i + 1;
+            }
+
+            offset += len;
+        }
+
+        return self.segments.len();
+    }
+
+    fn insert(&self, pos: usize, text: String) -> Self {
+        let mut doc = self.clone();
+        let i = doc.split(pos);
+        doc.segments.insert(i, Segment::Inserted(text));
+        return doc;
+    }
+
+    /// delete the original character at `pos`
+    fn delete(&self, pos: usize, len: usize) -> Option<Self> {
+        let mut doc = self.clone();
+        let i = doc.split(pos);
+
+        match doc.segments.get(i) {
+            Some(Segment::Original(start, end)) if end - start >= len => {
+                let (start, end) = (*start, *end);
+                doc.segments[i] = Segment::Original(start + len, end);
+                return Some(doc);
+            }
+            _ => return None,
+        }
+    }
+}
+
+struct Candidate<'a> {
+    doc: Document<'a>,
+    /// end of the edit in the candidate document
+    edit_end: usize,
+    error: Error,
+}
+
+/// the candidate repairs for an error at `pos` of the repaired input
+fn candidates<'a>(doc: &Document<'a>, repaired: &str, error: &Error) -> Vec<Candidate<'a>> {
+    let pos = error.span.0;
+    let original = doc.to_original(pos);
+
+    let mut candidates = Vec::new();
+
+    for expected in &error.expected {
+        if let Some(text) = unquote(expected) {
+            candidates.push(Candidate {
+                doc: doc.insert(pos, text.clone()),
+                edit_end: pos + text.len(),
+                error: Error::new(
+                    Span::new(original, original),
+                    format!("missing {}", expected),
+                )
+                .with_suggestion(Suggestion::new(Span::new(original, original), text)),
+            });
+        }
+    }
+
+    if let Some(c) = repaired.get(pos..).and_then(|s| s.chars().next()) {
+        if let Some(deleted) = doc.delete(pos, c.len_utf8()) {
+            let span = Span::new(original, original + c.len_utf8());
+            candidates.push(Candidate {
+                doc: deleted,
+                edit_end: pos,
+                error: Error::new(span, format!("unexpected {:?}", c))
+                    .with_suggestion(Suggestion::new(span, "")),
+            });
+        }
+    }
+
+    return candidates;
+}
+
+pub(crate) async fn parse_with_repair<T: Parse>(
+    text: &str,
+    max_repairs: usize,
+) -> (Option<T::Output>, Vec<Error>) {
+    let mut doc = Document::new(text);
+    let mut repaired = doc.render();
+    let mut repairs = Vec::new();
+
+    let (mut value, mut errors) = crate::parse_with_recovery::<T, _>(repaired.as_str()).await;
+
+    while value.is_none() && repairs.len() < max_repairs {
+        let fatal = match errors.last() {
+            Some(e) => e,
+            None => break,
+        };
+        let reached = doc.to_original(fatal.span.0);
+
+        let mut best: Option<(usize, Candidate, String, (Option<T::Output>, Vec<Error>))> = None;
+
+        for candidate in candidates(&doc, &repaired, fatal) {
+            let text = candidate.doc.render();
+            let re = crate::parse_with_recovery::<T, _>(text.as_str()).await;
+
+            // how far the parse gets with the repair applied,
+            // it must get past the edit to count as progress
+            let progress = match (&re.0, re.1.last()) {
+                (Some(_), _) => usize::MAX,
+                (None, Some(e)) if e.span.0 > candidate.edit_end => {
+                    candidate.doc.to_original(e.span.0)
+                }
+                _ => continue,
+            };
+
+            if progress <= reached {
+                continue;
+            }
+
+            if let Some((p, ..)) = &best {
+                if *p >= progress {
+                    continue;
+                }
+            }
+
+            best = Some((progress, candidate, text, re));
+        }
+
+        match best {
+            Some((_, candidate, text, re)) => {
+                doc = candidate.doc;
+                repaired = text;
+                repairs.push(candidate.error);
+                (value, errors) = re;
+            }
+            None => break,
+        }
+    }
+
+    // map the remaining errors back to the original input
+    for e in &mut errors {
+        e.span = doc.map_span(e.span);
+        if let Some(s) = &mut e.suggestion {
+            s.span = doc.map_span(s.span);
+        }
+    }
+
+    repairs.extend(errors);
+    repairs.sort_by_key(|e| e.span.0);
+
+    return (value, repairs);
+}
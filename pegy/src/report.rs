@@ -0,0 +1,219 @@
+use core::fmt::{Display, Formatter, Result, Write};
+
+use crate::{Error, Severity};
+
+/// number of columns a tab is expanded to
+const TAB_WIDTH: usize = 4;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// A human readable rendering of an `Error` or warning.
+///
+/// It takes the original text that was parsed and prints the
+/// offending line with line numbers, an underline below the span,
+/// the set of expected terms, the suggested fix and the help text if any.
+///
+/// ```text
+/// error: expected character ';'
+///  --> 2:10
+///   |
+/// 2 | let b = 2
+///   |          ^ expected ';'
+/// ```
+pub struct Report<'a> {
+    text: &'a str,
+    error: &'a Error,
+    name: Option<&'a str>,
+    color: bool,
+}
+
+impl<'a> Report<'a> {
+    pub const fn new(text: &'a str, error: &'a Error) -> Self {
+        Self {
+            text: text,
+            error: error,
+            name: None,
+            color: false,
+        }
+    }
+
+    /// name of the input, printed before the line and column
+    pub const fn with_name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// enable or disable ANSI colour output
+    pub const fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// return the 1-based line and column of the start of the error.
+    ///
+    /// The column is counted in characters, not bytes.
+    pub fn location(&self) -> (usize, usize) {
+        let offset = floor_char_boundary(self.text, self.error.span.0);
+        let line_start = line_start(self.text, offset);
+
+        let line = self.text.as_bytes()[..offset]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count();
+        let column = self.text[line_start..offset].chars().count();
+
+        return (line + 1, column + 1);
+    }
+
+    fn paint(&self, f: &mut Formatter<'_>, color: &str, s: &str) -> Result {
+        if self.color {
+            write!(f, "{}{}{}", color, s, RESET)
+        } else {
+            f.write_str(s)
+        }
+    }
+}
+
+impl<'a> Display for Report<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let start = floor_char_boundary(self.text, self.error.span.0);
+        let end = floor_char_boundary(self.text, self.error.span.1.max(self.error.span.0));
+
+        let line_start = line_start(self.text, start);
+        let line_end = match self.text[start..].find('\n') {
+            Some(i) => start + i,
+            None => self.text.len(),
+        };
+        let line_text = self.text[line_start..line_end].trim_end_matches('\r');
+
+        let (line, column) = self.location();
+
+        let mut line_no = itoa::Buffer::new();
+        let line_no = line_no.format(line);
+        let gutter = line_no.len();
+
+        let (title, color) = match self.error.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+        };
+
+        // header
+        self.paint(f, color, title)?;
+        if let Some(code) = &self.error.code {
+            if self.color {
+                write!(f, "{}[{}]{}", color, code, RESET)?;
+            } else {
+                write!(f, "[{}]", code)?;
+            }
+        }
+        if self.color {
+            write!(f, "{}: {}{}", BOLD, self.error.msg, RESET)?;
+        } else {
+            write!(f, ": {}", self.error.msg)?;
+        }
+        f.write_char('\n')?;
+
+        // location
+        write!(f, "{:gutter$}", "", gutter = gutter)?;
+        self.paint(f, BLUE, "--> ")?;
+        if let Some(name) = self.name {
+            write!(f, "{}:", name)?;
+        }
+        writeln!(f, "{}:{}", line, column)?;
+
+        write!(f, "{:gutter$} ", "", gutter = gutter)?;
+        self.paint(f, BLUE, "|")?;
+        f.write_char('\n')?;
+
+        // source line, tabs are expanded so that the underline lines up
+        self.paint(f, BLUE, line_no)?;
+        f.write_char(' ')?;
+        self.paint(f, BLUE, "|")?;
+        f.write_char(' ')?;
+        for c in line_text.chars() {
+            if c == '\t' {
+                write!(f, "{:TAB_WIDTH$}", "")?;
+            } else {
+                f.write_char(c)?;
+            }
+        }
+        f.write_char('\n')?;
+
+        // underline
+        let padding = display_width(&self.text[line_start..start]);
+        let underline = display_width(&self.text[start..end.min(line_end)]).max(1);
+
+        write!(f, "{:gutter$} ", "", gutter = gutter)?;
+        self.paint(f, BLUE, "|")?;
+        write!(f, " {:padding$}", "", padding = padding)?;
+
+        if self.color {
+            f.write_str(color)?;
+        }
+        for _ in 0..underline {
+            f.write_char('^')?;
+        }
+        if !self.error.expected.is_empty() {
+            write!(
+                f,
+                " {}",
+                crate::error::expected_message(&self.error.expected)
+            )?;
+        }
+        if self.color {
+            f.write_str(RESET)?;
+        }
+        f.write_char('\n')?;
+
+        if let Some(suggestion) = &self.error.suggestion {
+            let start = floor_char_boundary(self.text, suggestion.span.0);
+            let end = floor_char_boundary(self.text, suggestion.span.1.max(suggestion.span.0));
+            let replaced = &self.text[start..end];
+
+            write!(f, "{:gutter$} ", "", gutter = gutter)?;
+            self.paint(f, BLUE, "=")?;
+            if replaced.is_empty() {
+                writeln!(f, " suggestion: insert {:?}", suggestion.replacement)?;
+            } else if suggestion.replacement.is_empty() {
+                writeln!(f, " suggestion: remove {:?}", replaced)?;
+            } else {
+                writeln!(f, " suggestion: did you mean {:?}?", suggestion.replacement)?;
+            }
+        }
+
+        if let Some(help) = &self.error.help {
+            write!(f, "{:gutter$} ", "", gutter = gutter)?;
+            self.paint(f, BLUE, "=")?;
+            writeln!(f, " help: {}", help)?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// width of a str when printed, with tabs expanded
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+fn line_start(text: &str, offset: usize) -> usize {
+    match text[..offset].rfind('\n') {
+        Some(i) => i + 1,
+        None => 0,
+    }
+}
+
+/// clamp an offset into the text and move it back to a character boundary
+fn floor_char_boundary(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    return offset;
+}
@@ -0,0 +1,155 @@
+//! Fast scanning of byte runs.
+//!
+//! Every function has a `portable_simd` path under the `simd` feature,
+//! which handles 32 bytes at a time, and a stable word-at-a-time (SWAR)
+//! path that handles 8 bytes at a time. Both finish with a byte loop.
+
+#[cfg(feature = "simd")]
+use core::simd::{
+    cmp::{SimdPartialEq, SimdPartialOrd},
+    u8x32,
+};
+
+#[cfg(feature = "simd")]
+const LANES: usize = 32;
+
+const ONES: u64 = 0x0101_0101_0101_0101;
+const HIGH: u64 = 0x8080_8080_8080_8080;
+
+/// ascii whitespace as defined by `char::is_whitespace`,
+/// which includes the vertical tab unlike `u8::is_ascii_whitespace`.
+#[inline]
+pub(crate) const fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t'..=b'\r')
+}
+
+/// number of leading ascii whitespace bytes
+#[inline]
+pub(crate) fn whitespace_len(bytes: &[u8]) -> usize {
+    let mut i = 0;
+
+    #[cfg(feature = "simd")]
+    while i + LANES <= bytes.len() {
+        let v = u8x32::from_slice(&bytes[i..i + LANES]);
+        let matched = v.simd_eq(u8x32::splat(b' '))
+            | (v.simd_ge(u8x32::splat(b'\t')) & v.simd_le(u8x32::splat(b'\r')));
+
+        if !matched.all() {
+            return i + (!matched.to_bitmask()).trailing_zeros() as usize;
+        }
+        i += LANES;
+    }
+
+    while i + 8 <= bytes.len() {
+        let w = load(&bytes[i..]);
+        let matched = eq_mask(w, b' ') | range_mask(w, b'\t', b'\r');
+
+        let mismatched = !matched & HIGH;
+        if mismatched != 0 {
+            return i + (mismatched.trailing_zeros() / 8) as usize;
+        }
+        i += 8;
+    }
+
+    while i < bytes.len() && is_whitespace(bytes[i]) {
+        i += 1;
+    }
+    return i;
+}
+
+/// number of leading ascii decimal digits
+#[inline]
+pub(crate) fn digit_len(bytes: &[u8]) -> usize {
+    let mut i = 0;
+
+    #[cfg(feature = "simd")]
+    while i + LANES <= bytes.len() {
+        let v = u8x32::from_slice(&bytes[i..i + LANES]);
+        let matched = v.simd_ge(u8x32::splat(b'0')) & v.simd_le(u8x32::splat(b'9'));
+
+        if !matched.all() {
+            return i + (!matched.to_bitmask()).trailing_zeros() as usize;
+        }
+        i += LANES;
+    }
+
+    while i + 8 <= bytes.len() {
+        let w = load(&bytes[i..]);
+
+        let mismatched = !range_mask(w, b'0', b'9') & HIGH;
+        if mismatched != 0 {
+            return i + (mismatched.trailing_zeros() / 8) as usize;
+        }
+        i += 8;
+    }
+
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    return i;
+}
+
+/// position of the first byte equal to `a` or `b`
+#[inline]
+pub(crate) fn find_either(bytes: &[u8], a: u8, b: u8) -> Option<usize> {
+    let mut i = 0;
+
+    #[cfg(feature = "simd")]
+    while i + LANES <= bytes.len() {
+        let v = u8x32::from_slice(&bytes[i..i + LANES]);
+        let matched = v.simd_eq(u8x32::splat(a)) | v.simd_eq(u8x32::splat(b));
+
+        let matched = matched.to_bitmask();
+        if matched != 0 {
+            return Some(i + matched.trailing_zeros() as usize);
+        }
+        i += LANES;
+    }
+
+    while i + 8 <= bytes.len() {
+        let w = load(&bytes[i..]);
+
+        let matched = eq_mask(w, a) | eq_mask(w, b);
+        if matched != 0 {
+            return Some(i + (matched.trailing_zeros() / 8) as usize);
+        }
+        i += 8;
+    }
+
+    while i < bytes.len() {
+        if bytes[i] == a || bytes[i] == b {
+            return Some(i);
+        }
+        i += 1;
+    }
+    return None;
+}
+
+/// load 8 bytes, the first byte is the least significant
+#[inline]
+fn load(bytes: &[u8]) -> u64 {
+    let mut word = [0; 8];
+    word.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(word)
+}
+
+/// sets the high bit of every byte that is equal to `b`.
+#[inline]
+const fn eq_mask(w: u64, b: u8) -> u64 {
+    let x = w ^ (ONES * b as u64);
+    // the high bit of a byte is set by the addition or by `x` unless the byte is zero,
+    // clearing the high bits first keeps the addition from carrying into the next byte
+    let nonzero = ((x & !HIGH) + ONES * 0x7F) | x;
+    !nonzero & HIGH
+}
+
+/// sets the high bit of every byte in `lo..=hi`, both must be ascii.
+#[inline]
+const fn range_mask(w: u64, lo: u8, hi: u8) -> u64 {
+    // clearing the high bits keeps the additions from carrying into the next byte
+    let x = w & !HIGH;
+    let ge_lo = x + ONES * (0x80 - lo) as u64;
+    let gt_hi = x + ONES * (0x7F - hi) as u64;
+
+    ge_lo & !gt_hi & !w & HIGH
+}
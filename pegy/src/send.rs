@@ -0,0 +1,342 @@
+use core::future::Future;
+use core::ops::RangeInclusive;
+
+use crate::source::Character;
+use crate::{Error, FileId, MatchToken, Parse, Source, Span};
+
+/// A source that can be moved to another thread while it is parsed.
+///
+/// The methods are the `Send` versions of the async methods of `Source`.
+/// Only `peek_send` is required, the others default to the `Source` defaults
+/// built on `peek_send` and `peek_byte_send`. A source overrides them where
+/// it overrides the `Source` method, a source whose futures are `Send`
+/// may simply return them, e.g. `Source::peek(self)`.
+pub trait SourceSend: Source + Send {
+    fn peek_send(&mut self) -> impl Future<Output = Option<Character>> + Send;
+    fn peek_nth_send(&mut self, n: usize) -> impl Future<Output = Option<Character>> + Send {
+        async move { Defaults(self).peek_nth(n).await }
+    }
+    fn starts_with_send(&mut self, string: &str) -> impl Future<Output = bool> + Send {
+        async move { Defaults(self).starts_with(string).await }
+    }
+    fn match_str_send(&mut self, string: &str) -> impl Future<Output = bool> + Send {
+        async move { Defaults(self).match_str(string).await }
+    }
+    fn match_char_send(&mut self, ch: char) -> impl Future<Output = bool> + Send {
+        async move { Defaults(self).match_char(ch).await }
+    }
+    fn match_char_range_send(
+        &mut self,
+        r: RangeInclusive<char>,
+    ) -> impl Future<Output = Option<char>> + Send {
+        async move { Defaults(self).match_char_range(r).await }
+    }
+    fn take_while_send<F: FnMut(char) -> bool + Send>(
+        &mut self,
+        f: F,
+    ) -> impl Future<Output = Span> + Send {
+        async move { Defaults(self).take_while(f).await }
+    }
+    fn skip_while_send<F: FnMut(char) -> bool + Send>(
+        &mut self,
+        f: F,
+    ) -> impl Future<Output = usize> + Send {
+        async move { Defaults(self).skip_while(f).await }
+    }
+    fn skip_whitespace_send(&mut self) -> impl Future<Output = usize> + Send {
+        async move { Defaults(self).skip_whitespace().await }
+    }
+    fn take_digits_send(&mut self) -> impl Future<Output = Span> + Send {
+        async move { Defaults(self).take_digits().await }
+    }
+    fn take_until_either_send(&mut self, a: char, b: char) -> impl Future<Output = Span> + Send {
+        async move { Defaults(self).take_until_either(a, b).await }
+    }
+    fn peek_byte_send(&mut self) -> impl Future<Output = Option<u8>> + Send {
+        async { None }
+    }
+    fn match_bytes_send(&mut self, bytes: &[u8]) -> impl Future<Output = bool> + Send {
+        async move { Defaults(self).match_bytes(bytes).await }
+    }
+    fn match_byte_range_send(
+        &mut self,
+        r: RangeInclusive<u8>,
+    ) -> impl Future<Output = Option<u8>> + Send {
+        async move { Defaults(self).match_byte_range(r).await }
+    }
+    fn read_bytes_send(&mut self, buf: &mut [u8]) -> impl Future<Output = bool> + Send {
+        async move { Defaults(self).read_bytes(buf).await }
+    }
+}
+
+/// The version of `Parse` whose futures are `Send`, so that parsing
+/// can run in a task of a multi-threaded runtime, e.g. `tokio::spawn`.
+///
+/// `#[derive(ParseSend)]` generates it from the same grammar as `Parse`,
+/// every rule used in the grammar must implement `ParseSend` as well.
+pub trait ParseSend<K = core::convert::Infallible>: Parse<K> {
+    fn parse_send<S: SourceSend + MatchToken<K>>(
+        src: &mut S,
+    ) -> impl Future<Output = Result<Self::Output, Error>> + Send;
+    /// the `Send` version of `Parse::skip_many`
+    fn skip_many_send<S: SourceSend + MatchToken<K>>(
+        _src: &mut S,
+    ) -> impl Future<Output = Option<usize>> + Send {
+        async { None }
+    }
+}
+
+/// A source with only the primitives of a `SourceSend`,
+/// it provides the `Source` defaults of the other methods.
+struct Defaults<'a, S: SourceSend + ?Sized>(&'a mut S);
+
+impl<'a, S: SourceSend + ?Sized> Source for Defaults<'a, S> {
+    #[inline]
+    fn current_position(&self) -> usize {
+        self.0.current_position()
+    }
+    #[inline]
+    fn set_position(&mut self, pos: usize) {
+        self.0.set_position(pos)
+    }
+    #[inline]
+    async fn peek(&mut self) -> Option<Character> {
+        self.0.peek_send().await
+    }
+    #[inline]
+    async fn peek_byte(&mut self) -> Option<u8> {
+        self.0.peek_byte_send().await
+    }
+}
+
+/// A `Source` over a `SourceSend`.
+///
+/// Rules that only call the source parse through it in `ParseSend`,
+/// their futures are then `Send` because the source methods are.
+/// Tokens are not matched through it, and `take_while` and `skip_while`
+/// fall back to `peek` since their closures need not be `Send`.
+pub(crate) struct SendSource<'a, S: SourceSend>(pub &'a mut S);
+
+impl<'a, S: SourceSend> Source for SendSource<'a, S> {
+    #[inline]
+    fn current_position(&self) -> usize {
+        self.0.current_position()
+    }
+    #[inline]
+    fn set_position(&mut self, pos: usize) {
+        self.0.set_position(pos)
+    }
+    #[inline]
+    async fn peek(&mut self) -> Option<Character> {
+        self.0.peek_send().await
+    }
+    #[inline]
+    async fn peek_nth(&mut self, n: usize) -> Option<Character> {
+        self.0.peek_nth_send(n).await
+    }
+    #[inline]
+    async fn starts_with(&mut self, string: &str) -> bool {
+        self.0.starts_with_send(string).await
+    }
+    #[inline]
+    async fn match_str(&mut self, string: &str) -> bool {
+        self.0.match_str_send(string).await
+    }
+    #[inline]
+    async fn match_char(&mut self, ch: char) -> bool {
+        self.0.match_char_send(ch).await
+    }
+    #[inline]
+    async fn match_char_range(&mut self, r: RangeInclusive<char>) -> Option<char> {
+        self.0.match_char_range_send(r).await
+    }
+    #[inline]
+    async fn skip_whitespace(&mut self) -> usize {
+        self.0.skip_whitespace_send().await
+    }
+    #[inline]
+    async fn take_digits(&mut self) -> Span {
+        self.0.take_digits_send().await
+    }
+    #[inline]
+    async fn take_until_either(&mut self, a: char, b: char) -> Span {
+        self.0.take_until_either_send(a, b).await
+    }
+    #[inline]
+    async fn peek_byte(&mut self) -> Option<u8> {
+        self.0.peek_byte_send().await
+    }
+    #[inline]
+    async fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.0.match_bytes_send(bytes).await
+    }
+    #[inline]
+    async fn match_byte_range(&mut self, r: RangeInclusive<u8>) -> Option<u8> {
+        self.0.match_byte_range_send(r).await
+    }
+    #[inline]
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> bool {
+        self.0.read_bytes_send(buf).await
+    }
+    fn record_error(&mut self, error: Error) -> Result<(), Error> {
+        self.0.record_error(error)
+    }
+    #[inline]
+    fn slice(&self, span: Span) -> Option<&str> {
+        self.0.slice(span)
+    }
+    #[inline]
+    fn slice_bytes(&self, span: Span) -> Option<&[u8]> {
+        self.0.slice_bytes(span)
+    }
+    #[inline]
+    fn file(&self) -> Option<FileId> {
+        self.0.file()
+    }
+    fn take_input_error(&mut self) -> Option<Error> {
+        self.0.take_input_error()
+    }
+    fn commit(&mut self, pos: usize) {
+        self.0.commit(pos)
+    }
+    fn warn(&mut self, warning: Error) {
+        self.0.warn(warning)
+    }
+    fn recorded_len(&self) -> usize {
+        self.0.recorded_len()
+    }
+    fn truncate_recorded(&mut self, len: usize) {
+        self.0.truncate_recorded(len)
+    }
+}
+
+/// parse `T` through a `SendSource`
+pub(crate) async fn parse_through<T: Parse, S: SourceSend>(
+    src: &mut S,
+) -> Result<T::Output, Error> {
+    T::parse(&mut SendSource(src)).await
+}
+
+/// skip repetitions of `T` through a `SendSource`
+pub(crate) async fn skip_many_through<T: Parse, S: SourceSend>(src: &mut S) -> Option<usize> {
+    T::skip_many(&mut SendSource(src)).await
+}
+
+/// implements `ParseSend::parse_send` for a rule whose `parse` only calls the source
+macro_rules! parse_through_source {
+    () => {
+        fn parse_send<S: $crate::SourceSend>(
+            src: &mut S,
+        ) -> impl core::future::Future<Output = Result<Self::Output, $crate::Error>> + Send {
+            $crate::send::parse_through::<Self, S>(src)
+        }
+        fn skip_many_send<S: $crate::SourceSend>(
+            src: &mut S,
+        ) -> impl core::future::Future<Output = Option<usize>> + Send {
+            $crate::send::skip_many_through::<Self, S>(src)
+        }
+    };
+}
+
+/// implements the methods of `SourceSend` by calling the `Source` methods,
+/// for sources whose futures are known to be `Send`.
+macro_rules! forward_source_send {
+    () => {
+        #[inline]
+        fn peek_send(
+            &mut self,
+        ) -> impl core::future::Future<Output = Option<$crate::Character>> + Send {
+            $crate::Source::peek(self)
+        }
+        #[inline]
+        fn peek_nth_send(
+            &mut self,
+            n: usize,
+        ) -> impl core::future::Future<Output = Option<$crate::Character>> + Send {
+            $crate::Source::peek_nth(self, n)
+        }
+        #[inline]
+        fn starts_with_send(
+            &mut self,
+            string: &str,
+        ) -> impl core::future::Future<Output = bool> + Send {
+            $crate::Source::starts_with(self, string)
+        }
+        #[inline]
+        fn match_str_send(
+            &mut self,
+            string: &str,
+        ) -> impl core::future::Future<Output = bool> + Send {
+            $crate::Source::match_str(self, string)
+        }
+        #[inline]
+        fn match_char_send(&mut self, ch: char) -> impl core::future::Future<Output = bool> + Send {
+            $crate::Source::match_char(self, ch)
+        }
+        #[inline]
+        fn match_char_range_send(
+            &mut self,
+            r: core::ops::RangeInclusive<char>,
+        ) -> impl core::future::Future<Output = Option<char>> + Send {
+            $crate::Source::match_char_range(self, r)
+        }
+        #[inline]
+        fn take_while_send<F: FnMut(char) -> bool + Send>(
+            &mut self,
+            f: F,
+        ) -> impl core::future::Future<Output = $crate::Span> + Send {
+            $crate::Source::take_while(self, f)
+        }
+        #[inline]
+        fn skip_while_send<F: FnMut(char) -> bool + Send>(
+            &mut self,
+            f: F,
+        ) -> impl core::future::Future<Output = usize> + Send {
+            $crate::Source::skip_while(self, f)
+        }
+        #[inline]
+        fn skip_whitespace_send(&mut self) -> impl core::future::Future<Output = usize> + Send {
+            $crate::Source::skip_whitespace(self)
+        }
+        #[inline]
+        fn take_digits_send(&mut self) -> impl core::future::Future<Output = $crate::Span> + Send {
+            $crate::Source::take_digits(self)
+        }
+        #[inline]
+        fn take_until_either_send(
+            &mut self,
+            a: char,
+            b: char,
+        ) -> impl core::future::Future<Output = $crate::Span> + Send {
+            $crate::Source::take_until_either(self, a, b)
+        }
+        #[inline]
+        fn peek_byte_send(&mut self) -> impl core::future::Future<Output = Option<u8>> + Send {
+            $crate::Source::peek_byte(self)
+        }
+        #[inline]
+        fn match_bytes_send(
+            &mut self,
+            bytes: &[u8],
+        ) -> impl core::future::Future<Output = bool> + Send {
+            $crate::Source::match_bytes(self, bytes)
+        }
+        #[inline]
+        fn match_byte_range_send(
+            &mut self,
+            r: core::ops::RangeInclusive<u8>,
+        ) -> impl core::future::Future<Output = Option<u8>> + Send {
+            $crate::Source::match_byte_range(self, r)
+        }
+        #[inline]
+        fn read_bytes_send(
+            &mut self,
+            buf: &mut [u8],
+        ) -> impl core::future::Future<Output = bool> + Send {
+            $crate::Source::read_bytes(self, buf)
+        }
+    };
+}
+
+pub(crate) use forward_source_send;
+pub(crate) use parse_through_source;
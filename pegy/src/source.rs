@@ -1,8 +1,15 @@
+use core::convert::Infallible;
+use core::future::Future;
 use core::ops::RangeInclusive;
+#[cfg(feature = "futures")]
 use core::task::Poll;
 
 use alloc::vec::Vec;
 
+use crate::scan;
+use crate::send::forward_source_send;
+use crate::{Error, FileId, MatchToken, SourceSend, SourceSync, Span, Token};
+
 const CONT_MASK: u8 = 0x3F;
 
 /// Returns the initial codepoint accumulator for the first byte.
@@ -88,6 +95,30 @@ pub trait Source {
     fn set_position(&mut self, pos: usize);
     /// return the next character with codepoint and relative length
     async fn peek(&mut self) -> Option<Character>;
+    /// return the character `n` characters ahead without consuming anything,
+    /// `peek_nth(0)` is the same as `peek`.
+    async fn peek_nth(&mut self, n: usize) -> Option<Character> {
+        let start = self.current_position();
+        for _ in 0..n {
+            match self.peek().await {
+                Some(c) => self.set_position(self.current_position() + c.length),
+                None => {
+                    self.set_position(start);
+                    return None;
+                }
+            }
+        }
+        let c = self.peek().await;
+        self.set_position(start);
+        return c;
+    }
+    /// return true if the input continues with `string`, nothing is consumed.
+    async fn starts_with(&mut self, string: &str) -> bool {
+        let start = self.current_position();
+        let matched = self.match_str(string).await;
+        self.set_position(start);
+        return matched;
+    }
     async fn match_str(&mut self, string: &str) -> bool {
         let start = self.current_position();
         for c in string.chars() {
@@ -116,10 +147,143 @@ pub trait Source {
         }
         return None;
     }
+    /// consume the characters for which `f` returns true,
+    /// return the span consumed.
+    async fn take_while<F: FnMut(char) -> bool>(&mut self, mut f: F) -> Span {
+        let start = self.current_position();
+        while let Some(c) = self.peek().await {
+            if !f(c.ch) {
+                break;
+            }
+            self.set_position(self.current_position() + c.length);
+        }
+        return Span::new(start, self.current_position());
+    }
+    /// consume the characters for which `f` returns true,
+    /// return the number of characters consumed.
+    async fn skip_while<F: FnMut(char) -> bool>(&mut self, mut f: F) -> usize {
+        let mut count = 0;
+        self.take_while(|c| {
+            let matched = f(c);
+            count += matched as usize;
+            matched
+        })
+        .await;
+        return count;
+    }
+    /// consume whitespace as defined by `char::is_whitespace`,
+    /// return the number of characters consumed.
+    async fn skip_whitespace(&mut self) -> usize {
+        self.skip_while(char::is_whitespace).await
+    }
+    /// consume a run of ascii decimal digits, return the span consumed.
+    async fn take_digits(&mut self) -> Span {
+        self.take_while(|c| c.is_ascii_digit()).await
+    }
+    /// consume characters up to the first `a` or `b`, which is not consumed,
+    /// or up to the end of input. Return the span consumed.
+    async fn take_until_either(&mut self, a: char, b: char) -> Span {
+        self.take_while(|c| c != a && c != b).await
+    }
+    /// return the next byte, `None` at the end of input
+    /// or if the source is not byte addressable.
+    ///
+    /// Byte rules may leave the position inside a character.
+    async fn peek_byte(&mut self) -> Option<u8> {
+        None
+    }
+    /// consume `bytes` if the input continues with them.
+    async fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        let start = self.current_position();
+        for b in bytes {
+            if self.peek_byte().await != Some(*b) {
+                self.set_position(start);
+                return false;
+            }
+            self.set_position(self.current_position() + 1);
+        }
+        return true;
+    }
+    async fn match_byte_range(&mut self, r: RangeInclusive<u8>) -> Option<u8> {
+        if let Some(b) = self.peek_byte().await {
+            if r.contains(&b) {
+                self.set_position(self.current_position() + 1);
+                return Some(b);
+            }
+        }
+        return None;
+    }
+    /// fill `buf` with the next bytes and consume them,
+    /// nothing is consumed if the input is shorter.
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> bool {
+        let start = self.current_position();
+        for b in buf.iter_mut() {
+            match self.peek_byte().await {
+                Some(byte) => *b = byte,
+                None => {
+                    self.set_position(start);
+                    return false;
+                }
+            }
+            self.set_position(self.current_position() + 1);
+        }
+        return true;
+    }
+    /// record an error that a rule recovered from.
+    ///
+    /// Returns the error back if the source does not collect errors,
+    /// the rule should then fail with it instead of recovering.
+    fn record_error(&mut self, error: Error) -> Result<(), Error> {
+        Err(error)
+    }
+    /// return the text of a span, if the source retains it.
+    ///
+    /// The span must be made of positions obtained by calling `self.current_position`.
+    fn slice(&self, _span: Span) -> Option<&str> {
+        None
+    }
+    /// return the bytes of a span, if the source retains them.
+    fn slice_bytes(&self, _span: Span) -> Option<&[u8]> {
+        None
+    }
+    /// the file the source reads from, if it is bound to one
+    fn file(&self) -> Option<FileId> {
+        None
+    }
+    /// take the error that ended the input early,
+    /// if reading it failed or it is not valid utf8.
+    ///
+    /// The input then appears to end where the error occured,
+    /// the driver reports this error instead of the result of the parse.
+    fn take_input_error(&mut self) -> Option<Error> {
+        None
+    }
+    /// declare that the source will not be rewound before `pos`.
+    ///
+    /// Sources that stream their input may drop the text before it,
+    /// positions stay absolute. Spans before `pos` can no longer be sliced.
+    fn commit(&mut self, _pos: usize) {}
+    /// emit a warning, it is dropped if the source does not collect diagnostics.
+    ///
+    /// Warnings are recorded like errors, so a warning emitted
+    /// on a path that is backtracked is discarded as well.
+    fn warn(&mut self, _warning: Error) {}
+    /// return the number of errors and warnings recorded so far
+    fn recorded_len(&self) -> usize {
+        0
+    }
+    /// discard the errors and warnings recorded after the first `len`.
+    ///
+    /// Rules call this together with `set_position` when backtracking,
+    /// so that errors recovered on an abandoned path are not reported.
+    fn truncate_recorded(&mut self, _len: usize) {}
 }
 
-pub trait IntoSource {
-    type Source: Source;
+/// An input that can be parsed, e.g. a `&str`.
+///
+/// `K` is the token type of the source, see `MatchToken`.
+pub trait IntoSource<K = Infallible> {
+    type Source: MatchToken<K>;
     fn into(self) -> Self::Source;
 }
 
@@ -130,6 +294,7 @@ pub trait IntoSource {
 pub struct StrSource<'a> {
     s: &'a str,
     pos: usize,
+    file: Option<FileId>,
 }
 
 impl<'a> IntoSource for StrSource<'a> {
@@ -155,7 +320,17 @@ impl<'a> IntoSource for &'a str {
 
 impl<'a> StrSource<'a> {
     pub const fn new(s: &'a str) -> Self {
-        Self { s: s, pos: 0 }
+        Self {
+            s: s,
+            pos: 0,
+            file: None,
+        }
+    }
+
+    /// bind the source to a file, errors produced from it carry the file id
+    pub const fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
     }
 }
 
@@ -169,8 +344,76 @@ impl<'a> Source for StrSource<'a> {
         self.pos = pos;
     }
     #[inline]
+    fn slice(&self, span: Span) -> Option<&str> {
+        self.s.get(span.0..span.1)
+    }
+    #[inline]
+    fn slice_bytes(&self, span: Span) -> Option<&[u8]> {
+        self.s.as_bytes().get(span.0..span.1)
+    }
+    #[inline]
+    fn file(&self) -> Option<FileId> {
+        self.file
+    }
+    #[inline]
     async fn peek(&mut self) -> Option<Character> {
-        if self.pos == self.s.len() {
+        self.peek_sync()
+    }
+    #[inline]
+    async fn peek_nth(&mut self, n: usize) -> Option<Character> {
+        self.peek_nth_sync(n)
+    }
+    #[inline]
+    async fn starts_with(&mut self, string: &str) -> bool {
+        self.starts_with_sync(string)
+    }
+    #[inline]
+    async fn match_str(&mut self, string: &str) -> bool {
+        self.match_str_sync(string)
+    }
+    #[inline]
+    async fn peek_byte(&mut self) -> Option<u8> {
+        self.peek_byte_sync()
+    }
+    #[inline]
+    async fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.match_bytes_sync(bytes)
+    }
+    #[inline]
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> bool {
+        self.read_bytes_sync(buf)
+    }
+    #[inline]
+    async fn take_while<F: FnMut(char) -> bool>(&mut self, f: F) -> Span {
+        self.take_while_sync(f)
+    }
+    #[inline]
+    async fn skip_while<F: FnMut(char) -> bool>(&mut self, f: F) -> usize {
+        self.skip_while_sync(f)
+    }
+    #[inline]
+    async fn skip_whitespace(&mut self) -> usize {
+        self.skip_whitespace_sync()
+    }
+    #[inline]
+    async fn take_digits(&mut self) -> Span {
+        self.take_digits_sync()
+    }
+    #[inline]
+    async fn take_until_either(&mut self, a: char, b: char) -> Span {
+        self.take_until_either_sync(a, b)
+    }
+}
+
+impl<'a> SourceSend for StrSource<'a> {
+    forward_source_send!();
+}
+
+impl<'a> SourceSync for StrSource<'a> {
+    #[inline]
+    fn peek_sync(&mut self) -> Option<Character> {
+        // byte rules may leave the position inside a character
+        if self.pos == self.s.len() || !self.s.is_char_boundary(self.pos) {
             return None;
         }
 
@@ -187,7 +430,22 @@ impl<'a> Source for StrSource<'a> {
         }
     }
     #[inline]
-    async fn match_str(&mut self, string: &str) -> bool {
+    fn peek_nth_sync(&mut self, n: usize) -> Option<Character> {
+        let ch = self.s.get(self.pos..)?.chars().nth(n)?;
+        return Some(Character {
+            ch: ch,
+            length: ch.len_utf8(),
+        });
+    }
+    #[inline]
+    fn starts_with_sync(&mut self, string: &str) -> bool {
+        match self.s.as_bytes().get(self.pos..) {
+            Some(b) => b.starts_with(string.as_bytes()),
+            None => false,
+        }
+    }
+    #[inline]
+    fn match_str_sync(&mut self, string: &str) -> bool {
         if string.is_empty() {
             return true;
         }
@@ -221,21 +479,645 @@ impl<'a> Source for StrSource<'a> {
 
         return false;
     }
+    #[inline]
+    fn peek_byte_sync(&mut self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).copied()
+    }
+    #[inline]
+    fn match_bytes_sync(&mut self, bytes: &[u8]) -> bool {
+        match self.s.as_bytes().get(self.pos..) {
+            Some(b) if b.starts_with(bytes) => {
+                self.pos += bytes.len();
+                true
+            }
+            _ => false,
+        }
+    }
+    #[inline]
+    fn read_bytes_sync(&mut self, buf: &mut [u8]) -> bool {
+        match self.s.as_bytes().get(self.pos..self.pos + buf.len()) {
+            Some(b) => {
+                buf.copy_from_slice(b);
+                self.pos += buf.len();
+                true
+            }
+            None => false,
+        }
+    }
+    #[inline]
+    fn skip_whitespace_sync(&mut self) -> usize {
+        let mut count = 0;
+
+        loop {
+            // runs of ascii whitespace are scanned in bulk, every byte is a character
+            let len = scan::whitespace_len(&self.s.as_bytes()[self.pos..]);
+            self.pos += len;
+            count += len;
+
+            match self.s.as_bytes().get(self.pos) {
+                Some(b) if *b >= 0x80 => {
+                    let (end, n) = scan_while(self.s, self.pos, &mut |c| {
+                        !c.is_ascii() && c.is_whitespace()
+                    });
+                    if end == self.pos {
+                        break;
+                    }
+                    self.pos = end;
+                    count += n;
+                }
+                _ => break,
+            }
+        }
+
+        return count;
+    }
+    #[inline]
+    fn take_digits_sync(&mut self) -> Span {
+        let start = self.pos;
+        self.pos += scan::digit_len(&self.s.as_bytes()[self.pos..]);
+        return Span::new(start, self.pos);
+    }
+    #[inline]
+    fn take_until_either_sync(&mut self, a: char, b: char) -> Span {
+        let start = self.pos;
+
+        if a.is_ascii() && b.is_ascii() {
+            let rest = &self.s.as_bytes()[self.pos..];
+            self.pos += scan::find_either(rest, a as u8, b as u8).unwrap_or(rest.len());
+        } else {
+            self.pos = scan_while(self.s, self.pos, &mut |c| c != a && c != b).0;
+        }
+
+        return Span::new(start, self.pos);
+    }
+    #[inline]
+    fn take_while_sync<F: FnMut(char) -> bool>(&mut self, mut f: F) -> Span {
+        let start = self.pos;
+        self.pos = scan_while(self.s, self.pos, &mut f).0;
+        return Span::new(start, self.pos);
+    }
+    #[inline]
+    fn skip_while_sync<F: FnMut(char) -> bool>(&mut self, mut f: F) -> usize {
+        let (end, count) = scan_while(self.s, self.pos, &mut f);
+        self.pos = end;
+        return count;
+    }
+}
+
+/// scan the characters of `s` from `pos` while `f` returns true,
+/// return the end position and the number of characters scanned.
+#[inline]
+fn scan_while<F: FnMut(char) -> bool>(s: &str, mut pos: usize, f: &mut F) -> (usize, usize) {
+    let bytes = s.as_bytes();
+    let mut count = 0;
+
+    while let Some(&b) = bytes.get(pos) {
+        // ascii characters need no decoding
+        let (ch, length) = if b < 0x80 {
+            (b as char, 1)
+        } else {
+            match s.get(pos..).and_then(|rest| rest.chars().next()) {
+                Some(c) => (c, c.len_utf8()),
+                None => break,
+            }
+        };
+
+        if !f(ch) {
+            break;
+        }
+
+        pos += length;
+        count += 1;
+    }
+
+    return (pos, count);
+}
+
+/// A byte slice input source, for binary formats.
+///
+/// Every byte is read as the char of the same value, U+0000 to U+00FF,
+/// so char rules still apply. String literals are matched against
+/// their utf8 encoding.
+pub struct ByteSource<'a> {
+    b: &'a [u8],
+    pos: usize,
+    file: Option<FileId>,
+}
+
+impl<'a> IntoSource for ByteSource<'a> {
+    type Source = Self;
+    fn into(self) -> Self::Source {
+        self
+    }
+}
+
+impl<'a> IntoSource for &'a [u8] {
+    type Source = ByteSource<'a>;
+    fn into(self) -> Self::Source {
+        ByteSource::new(self)
+    }
+}
+
+impl<'a> ByteSource<'a> {
+    pub const fn new(b: &'a [u8]) -> Self {
+        Self {
+            b: b,
+            pos: 0,
+            file: None,
+        }
+    }
+
+    /// bind the source to a file, errors produced from it carry the file id
+    pub const fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+}
+
+impl<'a> Source for ByteSource<'a> {
+    #[inline]
+    fn current_position(&self) -> usize {
+        self.pos
+    }
+    #[inline]
+    fn set_position(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+    #[inline]
+    fn slice(&self, span: Span) -> Option<&str> {
+        core::str::from_utf8(self.b.get(span.0..span.1)?).ok()
+    }
+    #[inline]
+    fn slice_bytes(&self, span: Span) -> Option<&[u8]> {
+        self.b.get(span.0..span.1)
+    }
+    #[inline]
+    fn file(&self) -> Option<FileId> {
+        self.file
+    }
+    #[inline]
+    async fn peek(&mut self) -> Option<Character> {
+        let b = self.b.get(self.pos)?;
+        return Some(Character {
+            ch: char::from(*b),
+            length: 1,
+        });
+    }
+    #[inline]
+    async fn match_str(&mut self, string: &str) -> bool {
+        self.match_bytes(string.as_bytes()).await
+    }
+    #[inline]
+    async fn peek_byte(&mut self) -> Option<u8> {
+        self.b.get(self.pos).copied()
+    }
+    #[inline]
+    async fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        match self.b.get(self.pos..) {
+            Some(b) if b.starts_with(bytes) => {
+                self.pos += bytes.len();
+                true
+            }
+            _ => false,
+        }
+    }
+    #[inline]
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> bool {
+        match self.b.get(self.pos..self.pos + buf.len()) {
+            Some(b) => {
+                buf.copy_from_slice(b);
+                self.pos += buf.len();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<'a> SourceSync for ByteSource<'a> {}
+
+impl<'a> SourceSend for ByteSource<'a> {
+    forward_source_send!();
+}
+
+/// A source wrapper that records the errors recovered by rules
+/// and the warnings they emit.
+///
+/// Every other operation is forwarded to the inner source.
+pub struct RecordingSource<S: Source> {
+    src: S,
+    errors: Vec<Error>,
+    recover: bool,
+}
+
+impl<S: Source> IntoSource for RecordingSource<S> {
+    type Source = Self;
+    fn into(self) -> Self::Source {
+        self
+    }
+}
+
+impl<T: Token, S: MatchToken<T>> IntoSource<T> for RecordingSource<S> {
+    type Source = Self;
+    fn into(self) -> Self::Source {
+        self
+    }
+}
+
+impl<S: Source> RecordingSource<S> {
+    pub const fn new(src: S) -> Self {
+        Self {
+            src: src,
+            errors: Vec::new(),
+            recover: true,
+        }
+    }
+
+    /// create a source that only records warnings,
+    /// errors are not recovered from.
+    pub const fn without_recovery(src: S) -> Self {
+        Self {
+            src: src,
+            errors: Vec::new(),
+            recover: false,
+        }
+    }
+
+    /// the errors and warnings recorded so far
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// return the inner source and the recorded errors and warnings
+    pub fn into_parts(self) -> (S, Vec<Error>) {
+        (self.src, self.errors)
+    }
+}
+
+impl<S: Source> Source for RecordingSource<S> {
+    #[inline]
+    fn current_position(&self) -> usize {
+        self.src.current_position()
+    }
+    #[inline]
+    fn set_position(&mut self, pos: usize) {
+        self.src.set_position(pos)
+    }
+    #[inline]
+    async fn peek(&mut self) -> Option<Character> {
+        self.src.peek().await
+    }
+    #[inline]
+    async fn peek_nth(&mut self, n: usize) -> Option<Character> {
+        self.src.peek_nth(n).await
+    }
+    #[inline]
+    async fn starts_with(&mut self, string: &str) -> bool {
+        self.src.starts_with(string).await
+    }
+    #[inline]
+    async fn match_str(&mut self, string: &str) -> bool {
+        self.src.match_str(string).await
+    }
+    #[inline]
+    async fn match_char(&mut self, ch: char) -> bool {
+        self.src.match_char(ch).await
+    }
+    #[inline]
+    async fn match_char_range(&mut self, r: RangeInclusive<char>) -> Option<char> {
+        self.src.match_char_range(r).await
+    }
+    #[inline]
+    async fn take_while<F: FnMut(char) -> bool>(&mut self, f: F) -> Span {
+        self.src.take_while(f).await
+    }
+    #[inline]
+    async fn skip_while<F: FnMut(char) -> bool>(&mut self, f: F) -> usize {
+        self.src.skip_while(f).await
+    }
+    #[inline]
+    async fn skip_whitespace(&mut self) -> usize {
+        self.src.skip_whitespace().await
+    }
+    #[inline]
+    async fn take_digits(&mut self) -> Span {
+        self.src.take_digits().await
+    }
+    #[inline]
+    async fn take_until_either(&mut self, a: char, b: char) -> Span {
+        self.src.take_until_either(a, b).await
+    }
+    #[inline]
+    async fn peek_byte(&mut self) -> Option<u8> {
+        self.src.peek_byte().await
+    }
+    #[inline]
+    async fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.src.match_bytes(bytes).await
+    }
+    #[inline]
+    async fn match_byte_range(&mut self, r: RangeInclusive<u8>) -> Option<u8> {
+        self.src.match_byte_range(r).await
+    }
+    #[inline]
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> bool {
+        self.src.read_bytes(buf).await
+    }
+    fn record_error(&mut self, error: Error) -> Result<(), Error> {
+        if !self.recover {
+            return Err(error);
+        }
+        self.errors.push(error);
+        Ok(())
+    }
+    #[inline]
+    fn slice(&self, span: Span) -> Option<&str> {
+        self.src.slice(span)
+    }
+    #[inline]
+    fn slice_bytes(&self, span: Span) -> Option<&[u8]> {
+        self.src.slice_bytes(span)
+    }
+    #[inline]
+    fn file(&self) -> Option<FileId> {
+        self.src.file()
+    }
+    fn take_input_error(&mut self) -> Option<Error> {
+        self.src.take_input_error()
+    }
+    fn commit(&mut self, pos: usize) {
+        self.src.commit(pos)
+    }
+    fn warn(&mut self, warning: Error) {
+        self.errors.push(warning);
+    }
+    fn recorded_len(&self) -> usize {
+        self.errors.len()
+    }
+    fn truncate_recorded(&mut self, len: usize) {
+        self.errors.truncate(len);
+    }
+}
+
+impl<T: Token, S: MatchToken<T>> MatchToken<T> for RecordingSource<S> {
+    #[inline]
+    fn match_token<F: FnOnce(&T) -> bool>(&mut self, f: F) -> Option<T> {
+        self.src.match_token(f)
+    }
+}
+
+impl<S: SourceSync> SourceSync for RecordingSource<S> {}
+
+impl<S: SourceSend> SourceSend for RecordingSource<S> {
+    #[inline]
+    fn peek_send(&mut self) -> impl Future<Output = Option<Character>> + Send {
+        self.src.peek_send()
+    }
+    #[inline]
+    fn peek_nth_send(&mut self, n: usize) -> impl Future<Output = Option<Character>> + Send {
+        self.src.peek_nth_send(n)
+    }
+    #[inline]
+    fn starts_with_send(&mut self, string: &str) -> impl Future<Output = bool> + Send {
+        self.src.starts_with_send(string)
+    }
+    #[inline]
+    fn match_str_send(&mut self, string: &str) -> impl Future<Output = bool> + Send {
+        self.src.match_str_send(string)
+    }
+    #[inline]
+    fn match_char_send(&mut self, ch: char) -> impl Future<Output = bool> + Send {
+        self.src.match_char_send(ch)
+    }
+    #[inline]
+    fn match_char_range_send(
+        &mut self,
+        r: RangeInclusive<char>,
+    ) -> impl Future<Output = Option<char>> + Send {
+        self.src.match_char_range_send(r)
+    }
+    #[inline]
+    fn take_while_send<F: FnMut(char) -> bool + Send>(
+        &mut self,
+        f: F,
+    ) -> impl Future<Output = Span> + Send {
+        self.src.take_while_send(f)
+    }
+    #[inline]
+    fn skip_while_send<F: FnMut(char) -> bool + Send>(
+        &mut self,
+        f: F,
+    ) -> impl Future<Output = usize> + Send {
+        self.src.skip_while_send(f)
+    }
+    #[inline]
+    fn skip_whitespace_send(&mut self) -> impl Future<Output = usize> + Send {
+        self.src.skip_whitespace_send()
+    }
+    #[inline]
+    fn take_digits_send(&mut self) -> impl Future<Output = Span> + Send {
+        self.src.take_digits_send()
+    }
+    #[inline]
+    fn take_until_either_send(&mut self, a: char, b: char) -> impl Future<Output = Span> + Send {
+        self.src.take_until_either_send(a, b)
+    }
+    #[inline]
+    fn peek_byte_send(&mut self) -> impl Future<Output = Option<u8>> + Send {
+        self.src.peek_byte_send()
+    }
+    #[inline]
+    fn match_bytes_send(&mut self, bytes: &[u8]) -> impl Future<Output = bool> + Send {
+        self.src.match_bytes_send(bytes)
+    }
+    #[inline]
+    fn match_byte_range_send(
+        &mut self,
+        r: RangeInclusive<u8>,
+    ) -> impl Future<Output = Option<u8>> + Send {
+        self.src.match_byte_range_send(r)
+    }
+    #[inline]
+    fn read_bytes_send(&mut self, buf: &mut [u8]) -> impl Future<Output = bool> + Send {
+        self.src.read_bytes_send(buf)
+    }
+}
+
+/// A source wrapper that ends the input at `end`,
+/// used to parse length-prefixed data.
+pub(crate) struct BoundedSource<'s, S: Source> {
+    src: &'s mut S,
+    end: usize,
+}
+
+impl<'s, S: Source> BoundedSource<'s, S> {
+    pub fn new(src: &'s mut S, end: usize) -> Self {
+        Self { src: src, end: end }
+    }
+
+    /// return true if `len` more bytes fit before the end
+    fn fits(&self, len: usize) -> bool {
+        match self.src.current_position().checked_add(len) {
+            Some(end) => end <= self.end,
+            None => false,
+        }
+    }
+}
+
+impl<'s, S: Source> Source for BoundedSource<'s, S> {
+    #[inline]
+    fn current_position(&self) -> usize {
+        self.src.current_position()
+    }
+    #[inline]
+    fn set_position(&mut self, pos: usize) {
+        self.src.set_position(pos)
+    }
+    async fn peek(&mut self) -> Option<Character> {
+        let c = self.src.peek().await?;
+        if !self.fits(c.length) {
+            return None;
+        }
+        return Some(c);
+    }
+    async fn match_str(&mut self, string: &str) -> bool {
+        self.fits(string.len()) && self.src.match_str(string).await
+    }
+    async fn peek_byte(&mut self) -> Option<u8> {
+        if !self.fits(1) {
+            return None;
+        }
+        self.src.peek_byte().await
+    }
+    async fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.fits(bytes.len()) && self.src.match_bytes(bytes).await
+    }
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> bool {
+        self.fits(buf.len()) && self.src.read_bytes(buf).await
+    }
+    fn record_error(&mut self, error: Error) -> Result<(), Error> {
+        self.src.record_error(error)
+    }
+    fn slice(&self, span: Span) -> Option<&str> {
+        if span.1 > self.end {
+            return None;
+        }
+        self.src.slice(span)
+    }
+    fn slice_bytes(&self, span: Span) -> Option<&[u8]> {
+        if span.1 > self.end {
+            return None;
+        }
+        self.src.slice_bytes(span)
+    }
+    fn file(&self) -> Option<FileId> {
+        self.src.file()
+    }
+    fn take_input_error(&mut self) -> Option<Error> {
+        self.src.take_input_error()
+    }
+    fn commit(&mut self, pos: usize) {
+        self.src.commit(pos)
+    }
+    fn warn(&mut self, warning: Error) {
+        self.src.warn(warning)
+    }
+    fn recorded_len(&self) -> usize {
+        self.src.recorded_len()
+    }
+    fn truncate_recorded(&mut self, len: usize) {
+        self.src.truncate_recorded(len)
+    }
+}
+
+impl<'s, T: Token, S: MatchToken<T>> MatchToken<T> for BoundedSource<'s, S> {
+    fn match_token<F: FnOnce(&T) -> bool>(&mut self, f: F) -> Option<T> {
+        let start = self.src.current_position();
+
+        if start >= self.end {
+            return None;
+        }
+
+        let token = self.src.match_token(f)?;
+
+        if self.src.current_position() > self.end {
+            self.src.set_position(start);
+            return None;
+        }
+        return Some(token);
+    }
+}
+
+impl<'s, S: SourceSync> SourceSync for BoundedSource<'s, S> {}
+
+impl<'s, S: SourceSend> SourceSend for BoundedSource<'s, S> {
+    async fn peek_send(&mut self) -> Option<Character> {
+        let c = self.src.peek_send().await?;
+        if !self.fits(c.length) {
+            return None;
+        }
+        return Some(c);
+    }
+    async fn match_str_send(&mut self, string: &str) -> bool {
+        self.fits(string.len()) && self.src.match_str_send(string).await
+    }
+    async fn peek_byte_send(&mut self) -> Option<u8> {
+        if !self.fits(1) {
+            return None;
+        }
+        self.src.peek_byte_send().await
+    }
+    async fn match_bytes_send(&mut self, bytes: &[u8]) -> bool {
+        self.fits(bytes.len()) && self.src.match_bytes_send(bytes).await
+    }
+    async fn read_bytes_send(&mut self, buf: &mut [u8]) -> bool {
+        self.fits(buf.len()) && self.src.read_bytes_send(buf).await
+    }
+}
+
+/// How a source handles bytes that are not valid utf8.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Utf8Policy {
+    /// the input ends at the invalid bytes,
+    /// the parse fails with `ErrorKind::InvalidUtf8` at their offset.
+    #[default]
+    Strict,
+    /// each maximal invalid sequence is read as one U+FFFD
+    /// spanning the invalid bytes.
+    Lossy,
+    /// each invalid byte is read as the char of the same value,
+    /// U+0080 to U+00FF, with a length of one byte.
+    Bytes,
 }
 
 /// An utf8 async input source
 ///
 /// It takes in an `AsyncRead` and accumulates the content.
-/// An Eof will be emited once the `AsyncRead` returns an `Error`
-/// or when the next byte is not valid utf8.
+/// An Eof will be emited once the `AsyncRead` returns an `Error`,
+/// the `Error` is kept and reported with `ErrorKind::Io`.
+/// Invalid utf8 is handled according to the `Utf8Policy`.
+///
+/// The content before the position passed to `Source::commit`
+/// is dropped, so that memory stays bounded on long streams.
+/// Reading after the position is rewound before it panics.
 #[cfg(feature = "futures")]
 pub struct AsyncStrSource<T: futures::AsyncRead + Unpin> {
     reader: T,
+    /// the content read so far, starting at `buffer_start`
     pub buffer: Vec<u8>,
+    /// the position of the first byte in `buffer`
+    base: usize,
     pos: usize,
     is_eof: bool,
+    /// the error that ended the input
+    io_error: Option<std::io::Error>,
+    utf8_policy: Utf8Policy,
+    /// the first invalid sequence found under `Utf8Policy::Strict`
+    utf8_error: Option<Span>,
+    file: Option<FileId>,
 }
 
+#[cfg(feature = "futures")]
 impl<T: futures::AsyncRead + Unpin> IntoSource for AsyncStrSource<T> {
     type Source = Self;
     fn into(self) -> Self::Source {
@@ -249,65 +1131,131 @@ impl<T: futures::AsyncRead + Unpin> AsyncStrSource<T> {
         Self {
             reader,
             buffer: Vec::new(),
+            base: 0,
             pos: 0,
             is_eof: false,
+            io_error: None,
+            utf8_policy: Utf8Policy::Strict,
+            utf8_error: None,
+            file: None,
         }
     }
 
-    /// Reads the next code point out of a byte iterator (assuming a
-    /// UTF-8-like encoding).
-    ///
-    /// # Safety
+    /// bind the source to a file, errors produced from it carry the file id
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// set how bytes that are not valid utf8 are read
+    pub fn with_utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.utf8_policy = policy;
+        self
+    }
+
+    /// the position of the first byte still held in `buffer`
+    pub fn buffer_start(&self) -> usize {
+        self.base
+    }
+
+    /// the error that ended the input, if reading failed
+    pub fn io_error(&self) -> Option<&std::io::Error> {
+        self.io_error.as_ref()
+    }
+
+    /// decode the code point at the position.
     ///
-    /// `bytes` must produce a valid UTF-8-like (UTF-8 or WTF-8) string
+    /// Returns `Err` with the length of the maximal invalid subpart
+    /// if the bytes are not valid utf8, as `String::from_utf8_lossy` does.
     #[inline]
-    async fn next_code_point(&mut self) -> Option<(u32, usize)> {
-        let mut i = 1;
-
-        // Decode UTF-8
+    async fn next_code_point(&mut self) -> Option<Result<(u32, usize), usize>> {
         let x = self.next_u8(0).await?;
         if x < 128 {
-            return Some((x as u32, 1));
+            return Some(Ok((x as u32, 1)));
         }
 
-        // Multibyte case follows
-        // Decode from a byte combination out of: [[[x y] z] w]
-        // NOTE: Performance is sensitive to the exact formulation here
-        let init = utf8_first_byte(x, 2);
-        // SAFETY: `bytes` produces an UTF-8-like string,
-        // so the iterator must produce a value here.
-        let y = self.next_u8(1).await?;
-        i += 1;
+        // the range of the second byte depends on the first byte,
+        // which excludes overlong encodings, surrogates and code points above U+10FFFF
+        let (width, second) = match x {
+            0xC2..=0xDF => (2, 0x80..=0xBF),
+            0xE0 => (3, 0xA0..=0xBF),
+            0xE1..=0xEC | 0xEE..=0xEF => (3, 0x80..=0xBF),
+            0xED => (3, 0x80..=0x9F),
+            0xF0 => (4, 0x90..=0xBF),
+            0xF1..=0xF3 => (4, 0x80..=0xBF),
+            0xF4 => (4, 0x80..=0x8F),
+            _ => return Some(Err(1)),
+        };
 
-        let mut ch = utf8_acc_cont_byte(init, y);
-        if x >= 0xE0 {
-            // [[x y z] w] case
-            // 5th bit in 0xE0 .. 0xEF is always clear, so `init` is still valid
-            // SAFETY: `bytes` produces an UTF-8-like string,
-            // so the iterator must produce a value here.
-            let z = self.next_u8(2).await?;
-            i += 1;
+        let mut ch = utf8_first_byte(x, width);
+
+        for i in 1..width as usize {
+            let b = match self.next_u8(i).await {
+                Some(b) => b,
+                None => return Some(Err(i)),
+            };
 
-            let y_z = utf8_acc_cont_byte((y & CONT_MASK) as u32, z);
-            ch = init << 12 | y_z;
-            if x >= 0xF0 {
-                // [x y z w] case
-                // use only the lower 3 bits of `init`
-                // SAFETY: `bytes` produces an UTF-8-like string,
-                // so the iterator must produce a value here.
-                let w = self.next_u8(3).await?;
-                i += 1;
-
-                ch = (init & 7) << 18 | utf8_acc_cont_byte(y_z, w);
+            let valid = if i == 1 {
+                second.contains(&b)
+            } else {
+                b & !CONT_MASK == 0x80
+            };
+
+            if !valid {
+                return Some(Err(i));
             }
+
+            ch = utf8_acc_cont_byte(ch, b);
+        }
+
+        Some(Ok((ch, width as usize)))
+    }
+
+    /// the character at the position, with invalid utf8 handled by the policy
+    async fn next_char(&mut self) -> Option<Character> {
+        match self.next_code_point().await? {
+            Ok((c, l)) => Some(Character {
+                ch: unsafe { char::from_u32_unchecked(c) },
+                length: l,
+            }),
+            Err(l) => match self.utf8_policy {
+                Utf8Policy::Strict => {
+                    if self.utf8_error.is_none() {
+                        self.utf8_error = Some(Span::new(self.pos, self.pos + l));
+                    }
+                    None
+                }
+                Utf8Policy::Lossy => Some(Character {
+                    ch: char::REPLACEMENT_CHARACTER,
+                    length: l,
+                }),
+                Utf8Policy::Bytes => Some(Character {
+                    ch: char::from(self.next_u8(0).await?),
+                    length: 1,
+                }),
+            },
         }
+    }
 
-        Some((ch, i))
+    /// the offset of the position in `buffer`.
+    ///
+    /// Panics if the position is before the committed input,
+    /// the input there is dropped and must not read as the end of input.
+    #[inline]
+    fn buffer_offset(&self) -> usize {
+        match self.pos.checked_sub(self.base) {
+            Some(offset) => offset,
+            None => panic!(
+                "AsyncStrSource: position {} is before the committed input at {}, \
+                 a rule backtracked past a commit",
+                self.pos, self.base
+            ),
+        }
     }
 
     #[inline]
     async fn next_u8(&mut self, offset: usize) -> Option<u8> {
-        let pos = self.pos + offset;
+        let pos = self.buffer_offset() + offset;
 
         if self.buffer.len() == pos && self.is_eof {
             return None;
@@ -328,6 +1276,21 @@ impl<T: futures::AsyncRead + Unpin> AsyncStrSource<T> {
         }
     }
 
+    /// read until at least `len` bytes are buffered after the position,
+    /// return false if the input ends before.
+    async fn fill(&mut self, len: usize) -> bool {
+        let start = self.buffer_offset();
+
+        while self.buffer.len() < start + len {
+            if self.is_eof {
+                return false;
+            }
+
+            self.read_buf().await;
+        }
+        return true;
+    }
+
     #[allow(invalid_value)]
     #[inline]
     async fn read_buf(&mut self) -> Option<usize> {
@@ -337,12 +1300,19 @@ impl<T: futures::AsyncRead + Unpin> AsyncStrSource<T> {
             let mut buf: [u8; 512] = unsafe { core::mem::MaybeUninit::uninit().assume_init() };
 
             match self.reader.read(&mut buf).await {
+                Ok(0) => {
+                    self.is_eof = true;
+                }
                 Ok(l) => {
                     self.buffer.extend_from_slice(&buf[..l]);
                     return Some(l);
                 }
-                Err(_) => {
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+                    return Some(0);
+                }
+                Err(e) => {
                     self.is_eof = true;
+                    self.io_error = Some(e);
                 }
             };
         }
@@ -358,20 +1328,79 @@ impl<T: futures::AsyncRead + Unpin> Source for AsyncStrSource<T> {
     fn set_position(&mut self, pos: usize) {
         self.pos = pos;
     }
+    fn slice(&self, span: Span) -> Option<&str> {
+        let start = span.0.checked_sub(self.base)?;
+        let end = span.1.checked_sub(self.base)?;
+        core::str::from_utf8(self.buffer.get(start..end)?).ok()
+    }
+    fn slice_bytes(&self, span: Span) -> Option<&[u8]> {
+        let start = span.0.checked_sub(self.base)?;
+        let end = span.1.checked_sub(self.base)?;
+        self.buffer.get(start..end)
+    }
+    fn file(&self) -> Option<FileId> {
+        self.file
+    }
+    fn take_input_error(&mut self) -> Option<Error> {
+        if let Some(span) = self.utf8_error.take() {
+            return Some(Error::invalid_utf8(
+                span,
+                alloc::format!("invalid utf-8 sequence at byte {}", span.0),
+            ));
+        }
+
+        let e = self.io_error.take()?;
+        let end = self.base + self.buffer.len();
+        return Some(Error::io(
+            Span::new(end, end),
+            alloc::format!("failed to read input: {}", e),
+        ));
+    }
+    fn commit(&mut self, pos: usize) {
+        let end = self.base + self.buffer.len();
+        let drop = pos.min(end).saturating_sub(self.base);
+
+        // the prefix is only dropped once it is as long as the rest,
+        // so every byte is moved at most once on average
+        if drop == 0 || drop < self.buffer.len() - drop {
+            return;
+        }
+
+        self.buffer.drain(..drop);
+        self.base += drop;
+    }
 
     async fn peek(&mut self) -> Option<Character> {
-        if self.buffer.len() == self.pos && self.is_eof {
+        if self.base + self.buffer.len() == self.pos && self.is_eof {
             return None;
         }
 
-        if let Some((c, l)) = self.next_code_point().await {
-            return Some(Character {
-                ch: unsafe { char::from_u32_unchecked(c) },
-                length: l,
-            });
-        } else {
-            return None;
+        return self.next_char().await;
+    }
+
+    async fn peek_nth(&mut self, n: usize) -> Option<Character> {
+        // only the buffer is walked, the position is restored before returning
+        let start = self.pos;
+        for _ in 0..n {
+            match self.next_char().await {
+                Some(c) => self.pos += c.length,
+                None => {
+                    self.pos = start;
+                    return None;
+                }
+            }
         }
+        let c = self.peek().await;
+        self.pos = start;
+        return c;
+    }
+
+    async fn starts_with(&mut self, string: &str) -> bool {
+        if !self.fill(string.len()).await {
+            return false;
+        }
+        let start = self.buffer_offset();
+        return &self.buffer[start..start + string.len()] == string.as_bytes();
     }
 
     async fn match_str(&mut self, string: &str) -> bool {
@@ -379,21 +1408,44 @@ impl<T: futures::AsyncRead + Unpin> Source for AsyncStrSource<T> {
             return true;
         }
 
-        while (self.buffer.len() - self.pos) < string.len() {
-            if self.is_eof {
-                return false;
-            }
-
-            self.read_buf().await;
-        }
-
-        if (&self.buffer[self.pos..self.pos + string.len()]) == string.as_bytes() {
+        if self.starts_with(string).await {
             self.pos += string.len();
             return true;
         }
 
         return false;
     }
+
+    async fn peek_byte(&mut self) -> Option<u8> {
+        self.next_u8(0).await
+    }
+
+    async fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        if !self.fill(bytes.len()).await {
+            return false;
+        }
+        let start = self.buffer_offset();
+        if &self.buffer[start..start + bytes.len()] == bytes {
+            self.pos += bytes.len();
+            return true;
+        }
+        return false;
+    }
+
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> bool {
+        if !self.fill(buf.len()).await {
+            return false;
+        }
+        let start = self.buffer_offset();
+        buf.copy_from_slice(&self.buffer[start..start + buf.len()]);
+        self.pos += buf.len();
+        return true;
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T: futures::AsyncRead + Unpin + Send> SourceSend for AsyncStrSource<T> {
+    forward_source_send!();
 }
 
 /// An utility type that converts a `Stream` to `AsyncRead`.
@@ -404,6 +1456,9 @@ impl<T: futures::AsyncRead + Unpin> Source for AsyncStrSource<T> {
 /// stream exceeds the buffer provided by AsyncRead, it will
 /// fill the buffer and push the remaining bytes into its own buffer
 /// to be readed next time.
+///
+/// The end of the stream is reported as `Ok(0)`,
+/// an error of the stream is reported as an `io::Error`.
 #[cfg(feature = "futures")]
 pub struct AsyncStreamRead<
     S: futures::Stream<Item = Result<B, E>> + Unpin,
@@ -412,6 +1467,8 @@ pub struct AsyncStreamRead<
 > {
     buffer: Vec<u8>,
     stream: S,
+    /// the stream has ended, it is not polled again
+    is_done: bool,
 }
 
 #[cfg(feature = "futures")]
@@ -425,6 +1482,7 @@ impl<
         Self {
             buffer: Vec::new(),
             stream: stream,
+            is_done: false,
         }
     }
 }
@@ -465,6 +1523,10 @@ impl<
             return Poll::Ready(Ok(buffer_len));
         }
 
+        if self.is_done {
+            return Poll::Ready(Ok(0));
+        }
+
         let next = Stream::poll_next(core::pin::Pin::new(&mut self.stream), cx);
 
         match next {
@@ -472,6 +1534,12 @@ impl<
                 Some(Ok(bytes)) => {
                     let mut bytes = bytes.as_ref();
 
+                    // an empty chunk must not be read as the end of stream
+                    if bytes.is_empty() {
+                        cx.waker().wake_by_ref();
+                        return Poll::Pending;
+                    }
+
                     if bytes.len() > buf.len() {
                         self.buffer.extend_from_slice(&bytes[buf.len()..]);
                         bytes = &bytes[..buf.len()];
@@ -485,9 +1553,8 @@ impl<
                 }
                 Some(Err(e)) => return Poll::Ready(Err(std::io::Error::other(e))),
                 None => {
-                    return Poll::Ready(Err(std::io::Error::from(
-                        std::io::ErrorKind::UnexpectedEof,
-                    )))
+                    self.is_done = true;
+                    return Poll::Ready(Ok(0));
                 }
             },
             Poll::Pending => return Poll::Pending,
@@ -0,0 +1,259 @@
+use core::fmt::{Display, Formatter};
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Error, Report, Span, StrSource};
+
+/// Identifies a file registered in a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(pub u32);
+
+/// A `Span` in a specific file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileSpan {
+    pub file: FileId,
+    pub span: Span,
+}
+
+impl FileSpan {
+    pub const fn new(file: FileId, span: Span) -> Self {
+        Self {
+            file: file,
+            span: span,
+        }
+    }
+}
+
+/// A 0-based line and column.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// The offsets at which the lines of a text start.
+///
+/// Converts byte offsets to line and column in `O(log lines)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = alloc::vec![0];
+
+        for (i, b) in text.bytes().enumerate() {
+            if b == b'\n' {
+                line_starts.push(i + 1);
+            }
+        }
+
+        Self {
+            line_starts: line_starts,
+        }
+    }
+
+    /// number of lines, a text without newlines has one line
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// the offset at which a 0-based line starts
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
+    }
+
+    /// the 0-based line that contains the offset
+    pub fn line(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        }
+    }
+
+    /// line and column of an offset, the column is counted in UTF-8 bytes
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let line = self.line(offset);
+        LineCol {
+            line: line,
+            col: offset - self.line_starts[line],
+        }
+    }
+
+    /// line and column of an offset into `text`,
+    /// the column is counted in UTF-16 code units.
+    pub fn line_col_utf16(&self, text: &str, offset: usize) -> LineCol {
+        let line = self.line(offset);
+        let start = self.line_starts[line];
+        let end = offset.min(text.len());
+
+        let col = text
+            .get(start..end)
+            .map(|s| s.chars().map(char::len_utf16).sum())
+            .unwrap_or(end - start);
+
+        LineCol {
+            line: line,
+            col: col,
+        }
+    }
+
+    /// line and column of an offset into `text`,
+    /// the column is counted in characters.
+    pub fn line_col_chars(&self, text: &str, offset: usize) -> LineCol {
+        let line = self.line(offset);
+        let start = self.line_starts[line];
+        let mut end = offset.min(text.len());
+
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let col = text
+            .get(start..end)
+            .map(|s| s.chars().count())
+            .unwrap_or(end - start);
+
+        LineCol {
+            line: line,
+            col: col,
+        }
+    }
+
+    /// the offset of a line and column counted in UTF-8 bytes
+    pub fn offset(&self, line_col: LineCol) -> Option<usize> {
+        Some(self.line_start(line_col.line)? + line_col.col)
+    }
+}
+
+/// A named input registered in a `SourceMap`.
+pub struct SourceFile {
+    name: Cow<'static, str>,
+    text: String,
+    lines: LineIndex,
+}
+
+impl SourceFile {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn lines(&self) -> &LineIndex {
+        &self.lines
+    }
+
+    /// line and column of an offset, the column is counted in UTF-8 bytes
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        self.lines.line_col(offset)
+    }
+
+    /// line and column of an offset, the column is counted in UTF-16 code units
+    pub fn line_col_utf16(&self, offset: usize) -> LineCol {
+        self.lines.line_col_utf16(&self.text, offset)
+    }
+
+    /// line and column of an offset, the column is counted in characters
+    pub fn line_col_chars(&self, offset: usize) -> LineCol {
+        self.lines.line_col_chars(&self.text, offset)
+    }
+}
+
+/// A set of named inputs, e.g. a file and the files it includes.
+///
+/// ```
+/// use pegy::{FileSpan, SourceMap, Span};
+///
+/// let mut map = SourceMap::new();
+/// let file = map.add("main.cfg", "a = 1\nb = ?");
+///
+/// let span = FileSpan::new(file, Span::new(10, 11));
+/// assert_eq!(map.location(span).unwrap().to_string(), "main.cfg:2:5");
+///
+/// let err = pegy::parse_blocking::<u32, _>(map.source(file)).unwrap_err();
+/// assert_eq!(err.file_span(), Some(FileSpan::new(file, Span::new(0, 0))));
+/// ```
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub const fn new() -> Self {
+        Self { files: Vec::new() }
+    }
+
+    /// register an input and return its id
+    pub fn add<N, T>(&mut self, name: N, text: T) -> FileId
+    where
+        N: Into<Cow<'static, str>>,
+        T: Into<String>,
+    {
+        let text = text.into();
+        let id = FileId(self.files.len() as u32);
+
+        self.files.push(SourceFile {
+            name: name.into(),
+            lines: LineIndex::new(&text),
+            text: text,
+        });
+
+        return id;
+    }
+
+    pub fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file.0 as usize)
+    }
+
+    /// return a source over a registered input,
+    /// errors produced from it carry the file id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file is not registered in this map.
+    pub fn source(&self, file: FileId) -> StrSource<'_> {
+        let f = self.get(file).expect("file not registered in source map");
+        StrSource::new(&f.text).with_file(file)
+    }
+
+    /// the 1-based location of the start of a span,
+    /// the column is counted in characters like `Report::location`.
+    pub fn location(&self, span: FileSpan) -> Option<Location<'_>> {
+        let file = self.get(span.file)?;
+        let line_col = file.line_col_chars(span.span.0);
+
+        Some(Location {
+            name: file.name(),
+            line: line_col.line + 1,
+            column: line_col.col + 1,
+        })
+    }
+
+    /// render an error with the text and name of its file
+    pub fn report<'a>(&'a self, error: &'a Error) -> Option<Report<'a>> {
+        let file = self.get(error.file?)?;
+        Some(Report::new(file.text(), error).with_name(file.name()))
+    }
+}
+
+/// A 1-based line and column in a named file,
+/// displayed as `name:line:column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location<'a> {
+    pub name: &'a str,
+    pub line: usize,
+    /// column counted in characters
+    pub column: usize,
+}
+
+impl<'a> Display for Location<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}:{}", self.name, self.line, self.column)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{Error, IntoSource, MatchToken, Parse, Source, Span};
+
+struct State<S: Source> {
+    src: S,
+    is_first: bool,
+    is_done: bool,
+}
+
+impl<S: Source> State<S> {
+    /// parse the next item, `None` at the end of input or after an error
+    async fn next<T: Parse<K>, Sep: Parse<K>, K>(&mut self) -> Option<Result<T::Output, Error>>
+    where
+        S: MatchToken<K>,
+    {
+        if self.is_done {
+            return None;
+        }
+
+        let start = self.src.current_position();
+
+        if !self.is_first {
+            if let Err(e) = Sep::parse(&mut self.src).await {
+                if self.src.peek().await.is_none() {
+                    return self.end();
+                }
+                self.is_done = true;
+                return Some(Err(crate::finish_error(&mut self.src, e).await));
+            }
+        }
+
+        // a trailing separator ends the stream as well
+        if self.src.peek().await.is_none() {
+            return self.end();
+        }
+
+        match T::parse(&mut self.src).await {
+            Ok(v) => {
+                if let Some(e) = crate::input_error(&mut self.src) {
+                    self.is_done = true;
+                    return Some(Err(e));
+                }
+
+                let end = self.src.current_position();
+
+                // an item that matches nothing would be yielded forever
+                if end == start {
+                    self.is_done = true;
+                    return Some(Err(Error::new(
+                        Span::new(end, end),
+                        "item matched no input",
+                    )));
+                }
+
+                self.is_first = false;
+                self.src.commit(end);
+                Some(Ok(v))
+            }
+            Err(e) => {
+                self.is_done = true;
+                if let Some(e) = crate::input_error(&mut self.src) {
+                    return Some(Err(e));
+                }
+                Some(Err(crate::finish_error(&mut self.src, e).await))
+            }
+        }
+    }
+
+    /// end the stream, unless the input was cut off by an error
+    fn end<V>(&mut self) -> Option<Result<V, Error>> {
+        self.is_done = true;
+        Some(Err(crate::input_error(&mut self.src)?))
+    }
+}
+
+pub(crate) fn parse_stream<T: Parse<K>, Sep: Parse<K>, K>(
+    src: impl IntoSource<K>,
+) -> impl futures::Stream<Item = Result<T::Output, Error>> {
+    let state = State {
+        src: src.into(),
+        is_first: true,
+        is_done: false,
+    };
+
+    futures::stream::unfold(state, |mut state| async move {
+        let item = state.next::<T, Sep, K>().await?;
+        Some((item, state))
+    })
+}
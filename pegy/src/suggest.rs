@@ -0,0 +1,119 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::{unquote, Suggestion};
+use crate::{Error, Source, Span};
+
+/// the longest word that is compared against the expected literals
+const MAX_WORD_LEN: usize = 64;
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Attaches a "did you mean" suggestion to an error.
+///
+/// The word at the start of the error is compared against the string
+/// literals in the expected set and replaced by the closest one,
+/// if it is within a third of its length in edits.
+pub(crate) async fn did_you_mean<S: Source>(src: &mut S, error: Error) -> Error {
+    if error.suggestion.is_some() {
+        return error;
+    }
+
+    let literals = error
+        .expected
+        .iter()
+        .filter(|e| e.starts_with('"'))
+        .filter_map(|e| unquote(e))
+        .filter(|l| l.chars().all(is_word_char))
+        .collect::<Vec<_>>();
+
+    if literals.is_empty() {
+        return error;
+    }
+
+    // read the word at the error
+    let pos = src.current_position();
+    src.set_position(error.span.0);
+
+    let mut word = String::new();
+    while let Some(c) = src.peek().await {
+        if !is_word_char(c.ch) || word.len() >= MAX_WORD_LEN {
+            break;
+        }
+        word.push(c.ch);
+        src.set_position(src.current_position() + c.length);
+    }
+
+    let end = src.current_position();
+    src.set_position(pos);
+
+    if word.is_empty() {
+        return error;
+    }
+
+    let mut best: Option<(usize, String)> = None;
+
+    for literal in literals {
+        if literal == word {
+            continue;
+        }
+
+        let distance = edit_distance(&word, &literal);
+        let threshold = (literal.chars().count() / 3).max(1);
+
+        if distance > threshold {
+            continue;
+        }
+
+        if let Some((d, _)) = &best {
+            if *d <= distance {
+                continue;
+            }
+        }
+
+        best = Some((distance, literal));
+    }
+
+    match best {
+        Some((_, literal)) => {
+            let span = Span::new(error.span.0, end);
+            error.with_suggestion(Suggestion::new(span, literal))
+        }
+        None => error,
+    }
+}
+
+/// optimal string alignment distance, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // three rows of the distance matrix
+    let mut prev2 = alloc::vec![0; b.len() + 1];
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = alloc::vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            current[j] = (prev[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(prev[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(prev2[j - 2] + 1);
+            }
+        }
+
+        core::mem::swap(&mut prev2, &mut prev);
+        core::mem::swap(&mut prev, &mut current);
+    }
+
+    return prev[b.len()];
+}
@@ -0,0 +1,126 @@
+use core::future::Future;
+use core::ops::RangeInclusive;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use crate::source::Character;
+use crate::{Error, MatchToken, Parse, Source, Span};
+
+fn noop_raw_waker() -> RawWaker {
+    fn clone(_: *const ()) -> RawWaker {
+        noop_raw_waker()
+    }
+    fn noop(_: *const ()) {}
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    RawWaker::new(core::ptr::null(), &VTABLE)
+}
+
+/// poll a future that never pends, e.g. a parse over a `SourceSync`.
+///
+/// Panics if the future pends.
+pub(crate) fn poll_ready<F: Future>(f: F) -> F::Output {
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut f = core::pin::pin!(f);
+
+    match f.as_mut().poll(&mut cx) {
+        Poll::Ready(v) => v,
+        Poll::Pending => panic!("a synchronous source returned pending"),
+    }
+}
+
+/// run a future to completion on the current thread.
+///
+/// With the `futures` feature this is `futures::executor::block_on`,
+/// which parks the thread until the source wakes it.
+#[cfg(feature = "futures")]
+pub(crate) fn block_on<F: Future>(f: F) -> F::Output {
+    futures::executor::block_on(f)
+}
+
+/// run a future to completion on the current thread.
+///
+/// Without the `futures` feature there is no thread to park,
+/// the future is polled until it is ready. Sources such as `StrSource`
+/// never pend, so a parse over them completes in the first poll.
+#[cfg(not(feature = "futures"))]
+pub(crate) fn block_on<F: Future>(f: F) -> F::Output {
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut f = core::pin::pin!(f);
+
+    loop {
+        match f.as_mut().poll(&mut cx) {
+            Poll::Ready(v) => return v,
+            Poll::Pending => core::hint::spin_loop(),
+        }
+    }
+}
+
+/// A source that never pends, e.g. an in-memory input.
+///
+/// The methods are the synchronous versions of the async methods of `Source`.
+/// By default they poll the `Source` method once, a source overrides them
+/// where the `Source` method delegates to them instead.
+pub trait SourceSync: Source {
+    fn peek_sync(&mut self) -> Option<Character> {
+        poll_ready(self.peek())
+    }
+    fn peek_nth_sync(&mut self, n: usize) -> Option<Character> {
+        poll_ready(self.peek_nth(n))
+    }
+    fn starts_with_sync(&mut self, string: &str) -> bool {
+        poll_ready(self.starts_with(string))
+    }
+    fn match_str_sync(&mut self, string: &str) -> bool {
+        poll_ready(self.match_str(string))
+    }
+    fn match_char_sync(&mut self, ch: char) -> bool {
+        poll_ready(self.match_char(ch))
+    }
+    fn match_char_range_sync(&mut self, r: RangeInclusive<char>) -> Option<char> {
+        poll_ready(self.match_char_range(r))
+    }
+    fn take_while_sync<F: FnMut(char) -> bool>(&mut self, f: F) -> Span {
+        poll_ready(self.take_while(f))
+    }
+    fn skip_while_sync<F: FnMut(char) -> bool>(&mut self, f: F) -> usize {
+        poll_ready(self.skip_while(f))
+    }
+    fn skip_whitespace_sync(&mut self) -> usize {
+        poll_ready(self.skip_whitespace())
+    }
+    fn take_digits_sync(&mut self) -> Span {
+        poll_ready(self.take_digits())
+    }
+    fn take_until_either_sync(&mut self, a: char, b: char) -> Span {
+        poll_ready(self.take_until_either(a, b))
+    }
+    fn peek_byte_sync(&mut self) -> Option<u8> {
+        poll_ready(self.peek_byte())
+    }
+    fn match_bytes_sync(&mut self, bytes: &[u8]) -> bool {
+        poll_ready(self.match_bytes(bytes))
+    }
+    fn match_byte_range_sync(&mut self, r: RangeInclusive<u8>) -> Option<u8> {
+        poll_ready(self.match_byte_range(r))
+    }
+    fn read_bytes_sync(&mut self, buf: &mut [u8]) -> bool {
+        poll_ready(self.read_bytes(buf))
+    }
+}
+
+/// The synchronous version of `Parse`, for sources that never pend.
+///
+/// `#[derive(ParseSync)]` generates it from the same grammar as `Parse`,
+/// rules call their sub-rules directly without constructing futures.
+/// By default it polls `Parse::parse` once, which suits rules without sub-rules.
+pub trait ParseSync<K = core::convert::Infallible>: Parse<K> {
+    fn parse_sync<S: SourceSync + MatchToken<K>>(src: &mut S) -> Result<Self::Output, Error> {
+        poll_ready(Self::parse(src))
+    }
+    /// the synchronous version of `Parse::skip_many`
+    fn skip_many_sync<S: SourceSync + MatchToken<K>>(src: &mut S) -> Option<usize> {
+        poll_ready(Self::skip_many(src))
+    }
+}
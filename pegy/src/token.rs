@@ -0,0 +1,160 @@
+use core::convert::Infallible;
+
+use crate::send::forward_source_send;
+use crate::source::Character;
+use crate::{
+    Error, FileId, IntoSource, Parse, RecordingSource, Source, SourceSend, SourceSync, Span,
+};
+
+/// A token produced by an external lexer.
+///
+/// Tokens may borrow from the text they were lexed from, e.g. `Token<'src>`.
+pub trait Token: Clone {
+    /// the character offsets of the token in the original text
+    fn span(&self) -> Span;
+}
+
+/// A source whose input are tokens of type `T`.
+///
+/// Rules over tokens parse sources of their token type, e.g. a `TokenSource<T>`.
+/// Every source is one over `Infallible` tokens, which it never produces.
+pub trait MatchToken<T>: Source {
+    /// consume the next token if `f` returns true for it, return a clone of it
+    fn match_token<F: FnOnce(&T) -> bool>(&mut self, f: F) -> Option<T>;
+}
+
+impl<S: Source + ?Sized> MatchToken<Infallible> for S {
+    #[inline]
+    fn match_token<F: FnOnce(&Infallible) -> bool>(&mut self, _f: F) -> Option<Infallible> {
+        None
+    }
+}
+
+/// The token types whose sources a `RecordingSource` forwards,
+/// `Infallible` for text and every `Token`.
+#[allow(async_fn_in_trait)]
+pub trait RecordedToken: Sized {
+    /// parse `T` from a `RecordingSource` over tokens of this type
+    async fn parse_recording<T: Parse<Self>, S: MatchToken<Self>>(
+        src: &mut RecordingSource<S>,
+    ) -> Result<T::Output, Error>;
+}
+
+impl RecordedToken for Infallible {
+    async fn parse_recording<T: Parse<Self>, S: MatchToken<Self>>(
+        src: &mut RecordingSource<S>,
+    ) -> Result<T::Output, Error> {
+        T::parse(src).await
+    }
+}
+
+impl<K: Token> RecordedToken for K {
+    async fn parse_recording<T: Parse<Self>, S: MatchToken<Self>>(
+        src: &mut RecordingSource<S>,
+    ) -> Result<T::Output, Error> {
+        T::parse(src).await
+    }
+}
+
+/// A source over the tokens of an external lexer.
+///
+/// Grammar terms match tokens by pattern, e.g. `token(Token::Ident(_))`,
+/// and return a clone of the token. Positions are the offsets of the
+/// tokens in the original text, so spans of rules and errors point into it.
+/// The tokens must not be empty and must be in order.
+///
+/// Every token reads as one U+FFFC character, so that rules such as
+/// `EOF` and `ANY` work on tokens as well.
+pub struct TokenSource<'a, T: Token> {
+    tokens: &'a [T],
+    index: usize,
+    file: Option<FileId>,
+}
+
+impl<'a, T: Token> IntoSource<T> for TokenSource<'a, T> {
+    type Source = Self;
+    fn into(self) -> Self::Source {
+        self
+    }
+}
+
+impl<'a, T: Token> IntoSource<T> for &'a [T] {
+    type Source = TokenSource<'a, T>;
+    fn into(self) -> Self::Source {
+        TokenSource::new(self)
+    }
+}
+
+impl<'a, T: Token> TokenSource<'a, T> {
+    pub const fn new(tokens: &'a [T]) -> Self {
+        Self {
+            tokens: tokens,
+            index: 0,
+            file: None,
+        }
+    }
+
+    /// bind the source to a file, errors produced from it carry the file id
+    pub const fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// the tokens not consumed yet
+    pub fn rest(&self) -> &'a [T] {
+        &self.tokens[self.index..]
+    }
+
+    /// the position of the token at `index`, or the end of the last token
+    fn position_of(&self, index: usize) -> usize {
+        match self.tokens.get(index) {
+            Some(t) => t.span().0,
+            None => match self.tokens.last() {
+                Some(t) => t.span().1,
+                None => 0,
+            },
+        }
+    }
+}
+
+impl<'a, T: Token> Source for TokenSource<'a, T> {
+    #[inline]
+    fn current_position(&self) -> usize {
+        self.position_of(self.index)
+    }
+    #[inline]
+    fn set_position(&mut self, pos: usize) {
+        self.index = self.tokens.partition_point(|t| t.span().0 < pos);
+    }
+    #[inline]
+    fn file(&self) -> Option<FileId> {
+        self.file
+    }
+    async fn peek(&mut self) -> Option<Character> {
+        if self.index == self.tokens.len() {
+            return None;
+        }
+        return Some(Character {
+            ch: '\u{FFFC}',
+            length: self.position_of(self.index + 1) - self.position_of(self.index),
+        });
+    }
+}
+
+impl<'a, T: Token> MatchToken<T> for TokenSource<'a, T> {
+    fn match_token<F: FnOnce(&T) -> bool>(&mut self, f: F) -> Option<T> {
+        let token = self.tokens.get(self.index)?;
+
+        if f(token) {
+            self.index += 1;
+            return Some(token.clone());
+        }
+        return None;
+    }
+}
+
+impl<'a, T: Token> SourceSync for TokenSource<'a, T> {}
+
+impl<'a, T: Token + Sync> SourceSend for TokenSource<'a, T> {
+    forward_source_send!();
+}
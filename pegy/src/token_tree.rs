@@ -0,0 +1,443 @@
+//! Parsing `proc_macro2` token streams, e.g. the input of a proc macro.
+//!
+//! String literals of a grammar match an ident, a literal or a run of joint
+//! puncts with the same text, char literals match a punct. The rules of this module
+//! match any ident, punct, literal or group.
+//!
+//! The tokens of `proc_macro2` have no `Default`, which the fields of derived
+//! rules require, so the rules return them wrapped in an `Option` that is
+//! `Some` whenever the rule matches.
+//!
+//! Rules over token trees declare it with `#[parse(token = TokenTree)]`.
+//!
+//! ```
+//! use pegy::token_tree::{Ident, Parenthesized};
+//! use proc_macro2::TokenTree;
+//!
+//! #[derive(Debug, Default, pegy::Parse)]
+//! #[grammar("fn" $name:Ident $args:Parenthesized<Args> ';')]
+//! #[parse(token = TokenTree)]
+//! struct Decl {
+//!     name: Option<proc_macro2::Ident>,
+//!     args: Args,
+//! }
+//!
+//! #[derive(Debug, Default, pegy::Parse)]
+//! #[grammar($item0:Ident*)]
+//! #[parse(token = TokenTree)]
+//! struct Args(Vec<Option<proc_macro2::Ident>>);
+//!
+//! let tokens = "fn main(a b);".parse().unwrap();
+//! let decl = pegy::token_tree::parse_blocking::<Decl>(tokens).unwrap();
+//! assert_eq!(decl.name.unwrap(), "main");
+//! assert_eq!(decl.args.0.len(), 2);
+//! ```
+
+use core::marker::PhantomData;
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use proc_macro2::{Delimiter, Group, Spacing, TokenStream, TokenTree};
+
+use crate::source::Character;
+use crate::{Error, FileId, IntoSource, MatchToken, Parse, ParseSync, Source, SourceSync, Span};
+
+enum Entry {
+    Tree(TokenTree),
+    /// the start of a group, `close` is the index of its `Close`
+    Open {
+        tree: TokenTree,
+        close: usize,
+    },
+    Close(Group),
+}
+
+/// A source over the token trees of a `TokenStream`.
+///
+/// Positions are indices of tokens. The end of a group reads as the end of
+/// input, so that the rule of a group must match all of its content.
+/// Groups without delimiters, e.g. from `macro_rules` expansions, are transparent.
+///
+/// Each ident, literal and punct reads as one character, the first of its text,
+/// each group reads as U+FFFC.
+pub struct TokenTreeSource {
+    entries: Vec<Entry>,
+    pos: usize,
+    file: Option<FileId>,
+}
+
+impl IntoSource<TokenTree> for TokenTreeSource {
+    type Source = Self;
+    fn into(self) -> Self::Source {
+        self
+    }
+}
+
+impl TokenTreeSource {
+    pub fn new(tokens: TokenStream) -> Self {
+        let mut entries = Vec::new();
+        flatten(tokens, &mut entries);
+
+        Self {
+            entries: entries,
+            pos: 0,
+            file: None,
+        }
+    }
+
+    /// bind the source to a file, errors produced from it carry the file id
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// the span of the token at a position
+    pub fn span_at(&self, pos: usize) -> proc_macro2::Span {
+        match self.entries.get(pos) {
+            Some(Entry::Tree(t)) | Some(Entry::Open { tree: t, .. }) => t.span(),
+            Some(Entry::Close(g)) => g.span_close(),
+            None => match self.entries.last() {
+                Some(Entry::Tree(t)) => t.span(),
+                Some(Entry::Close(g)) => g.span_close(),
+                _ => proc_macro2::Span::call_site(),
+            },
+        }
+    }
+
+    /// convert an error produced from this source, it points at the token it starts at
+    pub fn syn_error(&self, error: &Error) -> syn::Error {
+        syn::Error::new(self.span_at(error.span.0), &error.msg)
+    }
+
+    fn current(&self) -> Option<&Entry> {
+        match self.entries.get(self.pos)? {
+            Entry::Close(_) => None,
+            entry => Some(entry),
+        }
+    }
+}
+
+fn flatten(tokens: TokenStream, entries: &mut Vec<Entry>) {
+    for tree in tokens {
+        match tree {
+            TokenTree::Group(g) if g.delimiter() == Delimiter::None => {
+                flatten(g.stream(), entries);
+            }
+            TokenTree::Group(g) => {
+                let open = entries.len();
+                entries.push(Entry::Open {
+                    tree: TokenTree::Group(g.clone()),
+                    close: 0,
+                });
+                flatten(g.stream(), entries);
+
+                let close = entries.len();
+                if let Entry::Open { close: c, .. } = &mut entries[open] {
+                    *c = close;
+                }
+                entries.push(Entry::Close(g));
+            }
+            tree => entries.push(Entry::Tree(tree)),
+        }
+    }
+}
+
+impl Source for TokenTreeSource {
+    #[inline]
+    fn current_position(&self) -> usize {
+        self.pos
+    }
+    #[inline]
+    fn set_position(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+    #[inline]
+    fn file(&self) -> Option<FileId> {
+        self.file
+    }
+    async fn peek(&mut self) -> Option<Character> {
+        let ch = match self.current()? {
+            Entry::Tree(TokenTree::Punct(p)) => p.as_char(),
+            Entry::Tree(t) => t.to_string().chars().next().unwrap_or('\u{FFFC}'),
+            _ => '\u{FFFC}',
+        };
+        return Some(Character { ch: ch, length: 1 });
+    }
+    async fn match_char(&mut self, ch: char) -> bool {
+        let matched = match self.current() {
+            Some(Entry::Tree(TokenTree::Punct(p))) => p.as_char() == ch,
+            Some(Entry::Tree(t)) => {
+                let mut buf = [0u8; 4];
+                t.to_string() == ch.encode_utf8(&mut buf) as &str
+            }
+            _ => false,
+        };
+
+        if matched {
+            self.pos += 1;
+        }
+        return matched;
+    }
+    async fn match_str(&mut self, string: &str) -> bool {
+        match self.current() {
+            Some(Entry::Tree(TokenTree::Punct(_))) => {
+                // a run of joint puncts, e.g. `=>` but not `= >`
+                let start = self.pos;
+                let mut chars = string.chars().peekable();
+                while let Some(ch) = chars.next() {
+                    let last = chars.peek().is_none();
+                    match self.current() {
+                        Some(Entry::Tree(TokenTree::Punct(p)))
+                            if p.as_char() == ch && (last || p.spacing() == Spacing::Joint) =>
+                        {
+                            self.pos += 1;
+                        }
+                        _ => {
+                            self.pos = start;
+                            return false;
+                        }
+                    }
+                }
+                return true;
+            }
+            Some(Entry::Tree(t)) if t.to_string() == string => {
+                self.pos += 1;
+                return true;
+            }
+            _ => return false,
+        }
+    }
+}
+
+impl MatchToken<TokenTree> for TokenTreeSource {
+    fn match_token<F: FnOnce(&TokenTree) -> bool>(&mut self, f: F) -> Option<TokenTree> {
+        let (tree, next) = match self.current()? {
+            Entry::Tree(t) => (t, self.pos + 1),
+            Entry::Open { tree, close } => (tree, close + 1),
+            Entry::Close(_) => return None,
+        };
+
+        if f(tree) {
+            let tree = tree.clone();
+            self.pos = next;
+            return Some(tree);
+        }
+        return None;
+    }
+}
+
+impl SourceSync for TokenTreeSource {}
+
+/// parse a `T` from all of the tokens, the error points at the token it starts at.
+pub async fn parse<T: Parse<TokenTree>>(tokens: TokenStream) -> syn::Result<T::Output> {
+    let mut src = TokenTreeSource::new(tokens);
+    let re = T::parse(&mut src).await;
+
+    match re {
+        Ok(v) => match src.current() {
+            None => Ok(v),
+            Some(_) => Err(syn::Error::new(
+                src.span_at(src.pos),
+                "unexpected trailing tokens",
+            )),
+        },
+        Err(e) => Err(src.syn_error(&e)),
+    }
+}
+
+pub fn parse_blocking<T: Parse<TokenTree>>(tokens: TokenStream) -> syn::Result<T::Output> {
+    crate::sync::block_on(parse::<T>(tokens))
+}
+
+/// the token tree at the position if it matches `f`
+async fn match_tree<S: MatchToken<TokenTree>>(
+    src: &mut S,
+    f: fn(&TokenTree) -> bool,
+    expected: &'static str,
+) -> Result<TokenTree, Error> {
+    match src.match_token(f) {
+        Some(t) => Ok(t),
+        None => {
+            let pos = src.current_position();
+            Err(
+                Error::new(Span::new(pos, pos), alloc::format!("expected {}", expected))
+                    .with_expected(expected),
+            )
+        }
+    }
+}
+
+/// Matches any ident.
+///
+/// The output is always `Some`, see the module documentation.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct Ident;
+
+impl Parse<TokenTree> for Ident {
+    type Output = Option<proc_macro2::Ident>;
+    async fn parse<S: MatchToken<TokenTree>>(src: &mut S) -> Result<Self::Output, Error> {
+        match match_tree(src, |t| matches!(t, TokenTree::Ident(_)), "identifier").await? {
+            TokenTree::Ident(i) => Ok(Some(i)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl ParseSync<TokenTree> for Ident {}
+
+/// Matches the punct `C`, e.g. `Punct<'#'>`.
+///
+/// The output is always `Some`, see the module documentation.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct Punct<const C: char>;
+
+impl<const C: char> Parse<TokenTree> for Punct<C> {
+    type Output = Option<proc_macro2::Punct>;
+    async fn parse<S: MatchToken<TokenTree>>(src: &mut S) -> Result<Self::Output, Error> {
+        let tree = src.match_token(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == C));
+
+        match tree {
+            Some(TokenTree::Punct(p)) => Ok(Some(p)),
+            _ => {
+                let pos = src.current_position();
+                Err(Error::new(
+                    Span::new(pos, pos),
+                    alloc::format!("expected '{}'", C),
+                ))
+            }
+        }
+    }
+    fn may_start_with(c: char) -> bool {
+        c == C
+    }
+}
+
+impl<const C: char> ParseSync<TokenTree> for Punct<C> {}
+
+/// Matches any literal.
+///
+/// The output is always `Some`, see the module documentation.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct Literal;
+
+impl Parse<TokenTree> for Literal {
+    type Output = Option<proc_macro2::Literal>;
+    async fn parse<S: MatchToken<TokenTree>>(src: &mut S) -> Result<Self::Output, Error> {
+        match match_tree(src, |t| matches!(t, TokenTree::Literal(_)), "literal").await? {
+            TokenTree::Literal(l) => Ok(Some(l)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl ParseSync<TokenTree> for Literal {}
+
+/// Matches any group with delimiters.
+///
+/// The output is always `Some`, see the module documentation.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct AnyGroup;
+
+impl Parse<TokenTree> for AnyGroup {
+    type Output = Option<Group>;
+    async fn parse<S: MatchToken<TokenTree>>(src: &mut S) -> Result<Self::Output, Error> {
+        match match_tree(src, |t| matches!(t, TokenTree::Group(_)), "group").await? {
+            TokenTree::Group(g) => Ok(Some(g)),
+            _ => unreachable!(),
+        }
+    }
+    fn may_start_with(c: char) -> bool {
+        c == '\u{FFFC}'
+    }
+}
+
+impl ParseSync<TokenTree> for AnyGroup {}
+
+/// parse `T` from the content of the group at the position, `T` must match all of it
+async fn parse_group<T: Parse<TokenTree>, S: MatchToken<TokenTree>>(
+    src: &mut S,
+    delimiter: Delimiter,
+    expected: &'static str,
+) -> Result<T::Output, Error> {
+    let start = src.current_position();
+    let group = src.match_token(|t| matches!(t, TokenTree::Group(g) if g.delimiter() == delimiter));
+
+    if group.is_none() {
+        return Err(Error::new(
+            Span::new(start, start),
+            alloc::format!("expected {}", expected),
+        )
+        .with_expected(expected));
+    }
+
+    // the content starts after the open delimiter
+    let end = src.current_position();
+    src.set_position(start + 1);
+
+    let value = match T::parse(src).await {
+        Ok(v) => v,
+        Err(e) => {
+            src.set_position(start);
+            return Err(e);
+        }
+    };
+
+    // the close delimiter is the last position of the group
+    let pos = src.current_position();
+    if pos != end - 1 {
+        src.set_position(start);
+        return Err(Error::new(Span::new(pos, pos), "unexpected token"));
+    }
+
+    src.set_position(end);
+    return Ok(value);
+}
+
+/// Matches a `( ... )` group and parses `T` from its content.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct Parenthesized<T>(PhantomData<T>);
+
+impl<T: Parse<TokenTree>> Parse<TokenTree> for Parenthesized<T> {
+    type Output = T::Output;
+    async fn parse<S: MatchToken<TokenTree>>(src: &mut S) -> Result<Self::Output, Error> {
+        parse_group::<T, S>(src, Delimiter::Parenthesis, "parentheses").await
+    }
+    fn may_start_with(c: char) -> bool {
+        c == '\u{FFFC}'
+    }
+}
+
+impl<T: Parse<TokenTree>> ParseSync<TokenTree> for Parenthesized<T> {}
+
+/// Matches a `[ ... ]` group and parses `T` from its content.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bracketed<T>(PhantomData<T>);
+
+impl<T: Parse<TokenTree>> Parse<TokenTree> for Bracketed<T> {
+    type Output = T::Output;
+    async fn parse<S: MatchToken<TokenTree>>(src: &mut S) -> Result<Self::Output, Error> {
+        parse_group::<T, S>(src, Delimiter::Bracket, "brackets").await
+    }
+    fn may_start_with(c: char) -> bool {
+        c == '\u{FFFC}'
+    }
+}
+
+impl<T: Parse<TokenTree>> ParseSync<TokenTree> for Bracketed<T> {}
+
+/// Matches a `{ ... }` group and parses `T` from its content.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct Braced<T>(PhantomData<T>);
+
+impl<T: Parse<TokenTree>> Parse<TokenTree> for Braced<T> {
+    type Output = T::Output;
+    async fn parse<S: MatchToken<TokenTree>>(src: &mut S) -> Result<Self::Output, Error> {
+        parse_group::<T, S>(src, Delimiter::Brace, "braces").await
+    }
+    fn may_start_with(c: char) -> bool {
+        c == '\u{FFFC}'
+    }
+}
+
+impl<T: Parse<TokenTree>> ParseSync<TokenTree> for Braced<T> {}
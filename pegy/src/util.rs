@@ -1,13 +1,18 @@
+use core::convert::Infallible;
+use core::future::Future;
 use core::marker::PhantomData;
+use core::pin::Pin;
 
 use alloc::{boxed::Box, vec::Vec};
 
-use crate::{Error, Parse, Span};
+use crate::combinator::combinator;
+use crate::send::parse_through_source;
+use crate::{Error, MatchToken, Parse, ParseSend, ParseSync, SourceSend, SourceSync, Span};
 
 #[derive(Debug, Default, PartialEq, Eq, Hash)]
 pub struct EOF;
 
-impl Parse for EOF {
+impl<K> Parse<K> for EOF {
     type Output = ();
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if src.peek().await.is_some() {
@@ -18,10 +23,16 @@ impl Parse for EOF {
     }
 }
 
+impl<K> ParseSync<K> for EOF {}
+
+impl<K> ParseSend<K> for EOF {
+    parse_through_source!();
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Hash)]
 pub struct SOF;
 
-impl Parse for SOF {
+impl<K> Parse<K> for SOF {
     type Output = ();
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if src.current_position() != 0 {
@@ -32,41 +43,104 @@ impl Parse for SOF {
     }
 }
 
+impl<K> ParseSync<K> for SOF {}
+
+impl<K> ParseSend<K> for SOF {
+    parse_through_source!();
+}
+
+/// Commits the current position, see `Source::commit`.
+///
+/// Matches nothing. The rule must not be backtracked past it afterwards.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct COMMIT;
+
+impl<K> Parse<K> for COMMIT {
+    type Output = ();
+    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
+        src.commit(src.current_position());
+        return Ok(());
+    }
+}
+
+impl<K> ParseSync<K> for COMMIT {}
+
+impl<K> ParseSend<K> for COMMIT {
+    parse_through_source!();
+}
+
 #[derive(Debug, Default, PartialEq, Eq)]
-pub struct Recursive<T: Parse>(pub Box<T::Output>);
+pub struct Recursive<T: Parse<K>, K = Infallible>(pub Box<T::Output>);
 
-impl<T: Parse> Parse for Recursive<T> {
+impl<K, T: Parse<K>> Parse<K> for Recursive<T, K> {
     type Output = T::Output;
-    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, crate::Error> {
+    async fn parse<S: MatchToken<K>>(src: &mut S) -> Result<Self::Output, crate::Error> {
         let f = Box::pin(T::parse(src));
         return f.await;
     }
+    fn may_start_with(c: char) -> bool {
+        T::may_start_with(c)
+    }
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
-pub struct Boxed<T: Parse>(PhantomData<T>);
+impl<K, T: ParseSync<K>> ParseSync<K> for Recursive<T, K> {
+    fn parse_sync<S: SourceSync + MatchToken<K>>(src: &mut S) -> Result<Self::Output, Error> {
+        T::parse_sync(src)
+    }
+}
 
-impl<T: Parse> Parse for Boxed<T> {
-    type Output = Box<T::Output>;
-    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
-        let value = T::parse(src).await?;
+impl<K: 'static, T: ParseSend<K> + 'static> ParseSend<K> for Recursive<T, K> {
+    fn parse_send<S: SourceSend + MatchToken<K>>(
+        src: &mut S,
+    ) -> impl Future<Output = Result<Self::Output, Error>> + Send {
+        // a trait object, so that the future of a rule does not contain its own type
+        let f: Pin<Box<dyn Future<Output = Result<T::Output, Error>> + Send + '_>> =
+            Box::pin(T::parse_send(src));
+        return f;
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Boxed<T>(PhantomData<T>);
+
+combinator! {
+    impl[K, T] Boxed<T>;
+    Parse<K> where [T: Parse<K>]
+    ParseSync<K> where [T: ParseSync<K>]
+    ParseSend<K> where [T: ParseSend<K>]
+    {
+        type Output = Box<T::Output>;
+        fn may_start_with(c: char) -> bool {
+            T::may_start_with(c)
+        }
+    }
+    fn parse(src) -> Result<Self::Output, Error> {
+        let value = parse!(T, src)?;
         Ok(Box::new(value))
     }
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct Repeat<
-    T: Parse,
+    T: Parse<K>,
     const MIN: usize = 0,
     const MAX: usize = 18446744073709551615,
     const SEP: u32 = 4294967295,
+    K = Infallible,
 >(pub Vec<T::Output>);
 
-impl<T: Parse, const MIN: usize, const MAX: usize, const SEP: u32> Parse
-    for Repeat<T, MIN, MAX, SEP>
-{
-    type Output = Vec<T::Output>;
-    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, crate::Error> {
+combinator! {
+    impl[K, T, const MIN: usize, const MAX: usize, const SEP: u32] Repeat<T, MIN, MAX, SEP, K>;
+    Parse<K> where [T: Parse<K>]
+    ParseSync<K> where [T: ParseSync<K>]
+    ParseSend<K> where [T: ParseSend<K>, T::Output: Send]
+    {
+        type Output = Vec<T::Output>;
+        fn may_start_with(c: char) -> bool {
+            MIN == 0 || MAX == 0 || T::may_start_with(c)
+        }
+    }
+    fn parse(src) -> Result<Self::Output, Error> {
         let seperator = char::from_u32(SEP);
 
         let mut v = Vec::new();
@@ -77,7 +151,7 @@ impl<T: Parse, const MIN: usize, const MAX: usize, const SEP: u32> Parse
 
         let start = src.current_position();
 
-        while let Ok(value) = T::parse(src).await {
+        while let Ok(value) = parse!(T, src) {
             v.push(value);
 
             if v.len() == MAX {
@@ -85,7 +159,7 @@ impl<T: Parse, const MIN: usize, const MAX: usize, const SEP: u32> Parse
             }
 
             if let Some(ch) = seperator {
-                if !src.match_char(ch).await {
+                if !match_char!(src, ch) {
                     break;
                 }
             }
@@ -105,17 +179,24 @@ impl<T: Parse, const MIN: usize, const MAX: usize, const SEP: u32> Parse
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct RepeatQuiet<
-    T: Parse,
+    T,
     const MIN: usize = 0,
     const MAX: usize = 18446744073709551615,
     const SEP: u32 = 4294967295,
 >(PhantomData<T>);
 
-impl<T: Parse, const MIN: usize, const MAX: usize, const SEP: u32> Parse
-    for RepeatQuiet<T, MIN, MAX, SEP>
-{
-    type Output = ();
-    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, crate::Error> {
+combinator! {
+    impl[K, T, const MIN: usize, const MAX: usize, const SEP: u32] RepeatQuiet<T, MIN, MAX, SEP>;
+    Parse<K> where [T: Parse<K>]
+    ParseSync<K> where [T: ParseSync<K>]
+    ParseSend<K> where [T: ParseSend<K>]
+    {
+        type Output = ();
+        fn may_start_with(c: char) -> bool {
+            MIN == 0 || MAX == 0 || T::may_start_with(c)
+        }
+    }
+    fn parse(src) -> Result<Self::Output, Error> {
         let seperator = char::from_u32(SEP);
 
         if MAX == 0 {
@@ -126,14 +207,29 @@ impl<T: Parse, const MIN: usize, const MAX: usize, const SEP: u32> Parse
 
         let start = src.current_position();
 
-        while let Ok(_) = T::parse(src).await {
+        // unbounded repetitions without separator can be skipped in bulk
+        if MAX == usize::MAX && seperator.is_none() {
+            if let Some(n) = skip_many!(T, src) {
+                if n < MIN {
+                    let end = src.current_position();
+                    src.set_position(start);
+                    return Err(Error::new(
+                        Span::new(start, end),
+                        "expected minimal number of repeats",
+                    ));
+                }
+                return Ok(());
+            }
+        }
+
+        while parse!(T, src).is_ok() {
             i += 1;
             if i == MAX {
                 break;
             }
 
             if let Some(ch) = seperator {
-                if !src.match_char(ch).await {
+                if !match_char!(src, ch) {
                     break;
                 }
             }
@@ -151,52 +247,115 @@ impl<T: Parse, const MIN: usize, const MAX: usize, const SEP: u32> Parse
     }
 }
 
-pub struct AND<A: Parse, B: Parse>(A::Output, B::Output);
+pub struct AND<A: Parse<K>, B: Parse<K>, K = Infallible>(A::Output, B::Output);
 
-impl<A: Parse, B: Parse> Default for AND<A, B> {
+impl<K, A: Parse<K>, B: Parse<K>> Default for AND<A, B, K> {
     fn default() -> Self {
         Self(A::Output::default(), B::Output::default())
     }
 }
 
-impl<A: Parse, B: Parse> Parse for AND<A, B> {
-    type Output = Self;
-    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
-        let a = A::parse(src).await?;
+combinator! {
+    impl[K, A, B] AND<A, B, K>;
+    Parse<K> where [A: Parse<K>, B: Parse<K>]
+    ParseSync<K> where [A: ParseSync<K>, B: ParseSync<K>]
+    ParseSend<K> where [A: ParseSend<K>, B: ParseSend<K>, A::Output: Send]
+    {
+        type Output = Self;
+        fn may_start_with(c: char) -> bool {
+            A::may_start_with(c)
+        }
+    }
+    fn parse(src) -> Result<Self::Output, Error> {
+        let a = parse!(A, src)?;
 
-        let b = B::parse(src).await?;
+        let b = parse!(B, src)?;
         return Ok(Self(a, b));
     }
 }
 
 #[derive(Debug)]
-pub enum OR<A: Parse, B: Parse> {
+pub enum OR<A: Parse<K>, B: Parse<K>, K = Infallible> {
     A(A::Output),
     B(B::Output),
 }
 
-impl<A: Parse, B: Parse> Default for OR<A, B> {
+impl<K, A: Parse<K>, B: Parse<K>> Default for OR<A, B, K> {
     fn default() -> Self {
         Self::A(A::Output::default())
     }
 }
 
-impl<A: Parse, B: Parse> Parse for OR<A, B> {
-    type Output = Self;
-    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
-        if let Ok(a) = A::parse(src).await {
+combinator! {
+    impl[K, A, B] OR<A, B, K>;
+    Parse<K> where [A: Parse<K>, B: Parse<K>]
+    ParseSync<K> where [A: ParseSync<K>, B: ParseSync<K>]
+    ParseSend<K> where [A: ParseSend<K>, B: ParseSend<K>]
+    {
+        type Output = Self;
+        fn may_start_with(c: char) -> bool {
+            A::may_start_with(c) || B::may_start_with(c)
+        }
+    }
+    fn parse(src) -> Result<Self::Output, Error> {
+        if let Ok(a) = parse!(A, src) {
             return Ok(Self::A(a));
         }
 
-        let b = B::parse(src).await?;
+        let b = parse!(B, src)?;
         return Ok(Self::B(b));
     }
 }
 
+/// Parses `T`, recovering from its failure.
+///
+/// If `T` fails, the error is recorded, the input is skipped until `U`
+/// matches or the input ends and the default value is returned. `U` is not consumed.
+/// If the source does not record errors, the error of `T` is returned instead.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Recover<T, U>(PhantomData<(T, U)>);
+
+combinator! {
+    impl[K, T, U] Recover<T, U>;
+    Parse<K> where [T: Parse<K>, U: Parse<K>]
+    ParseSync<K> where [T: ParseSync<K>, U: ParseSync<K>]
+    ParseSend<K> where [T: ParseSend<K>, U: ParseSend<K>]
+    {
+        type Output = T::Output;
+    }
+    fn parse(src) -> Result<Self::Output, Error> {
+        let start = src.current_position();
+        let recorded = src.recorded_len();
+
+        let e = match parse!(T, src) {
+            Ok(v) => return Ok(v),
+            Err(e) => e,
+        };
+
+        src.set_position(start);
+        src.truncate_recorded(recorded);
+        src.record_error(e)?;
+
+        loop {
+            let pos = src.current_position();
+            if parse!(U, src).is_ok() {
+                src.set_position(pos);
+                break;
+            }
+            match peek!(src) {
+                Some(ch) => src.set_position(pos + ch.length),
+                None => break,
+            }
+        }
+
+        return Ok(T::Output::default());
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ANY;
 
-impl Parse for ANY {
+impl<K> Parse<K> for ANY {
     type Output = char;
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if let Some(ch) = src.peek().await {
@@ -211,10 +370,40 @@ impl Parse for ANY {
     }
 }
 
+impl<K> ParseSync<K> for ANY {}
+
+impl<K> ParseSend<K> for ANY {
+    parse_through_source!();
+}
+
+/// Matches any byte, returns `u8`.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BYTE;
+
+impl<K> Parse<K> for BYTE {
+    type Output = u8;
+    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
+        if let Some(b) = src.peek_byte().await {
+            src.set_position(src.current_position() + 1);
+            return Ok(b);
+        }
+
+        let pos = src.current_position();
+
+        return Err(Error::new(Span::new(pos, pos), "expected byte"));
+    }
+}
+
+impl<K> ParseSync<K> for BYTE {}
+
+impl<K> ParseSend<K> for BYTE {
+    parse_through_source!();
+}
+
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct WHITESPACE;
 
-impl Parse for WHITESPACE {
+impl<K> Parse<K> for WHITESPACE {
     type Output = char;
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if let Some(ch) = src.peek().await {
@@ -229,12 +418,30 @@ impl Parse for WHITESPACE {
 
         return Err(Error::new(Span::new(pos, pos), "expected whitespace"));
     }
+    #[inline]
+    async fn skip_many<S: crate::Source>(src: &mut S) -> Option<usize> {
+        Some(src.skip_whitespace().await)
+    }
+    fn may_start_with(c: char) -> bool {
+        c.is_whitespace()
+    }
+}
+
+impl<K> ParseSync<K> for WHITESPACE {
+    #[inline]
+    fn skip_many_sync<S: crate::SourceSync>(src: &mut S) -> Option<usize> {
+        Some(src.skip_whitespace_sync())
+    }
+}
+
+impl<K> ParseSend<K> for WHITESPACE {
+    parse_through_source!();
 }
 
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ALPHABETIC;
 
-impl Parse for ALPHABETIC {
+impl<K> Parse<K> for ALPHABETIC {
     type Output = char;
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if let Some(ch) = src.peek().await {
@@ -252,12 +459,30 @@ impl Parse for ALPHABETIC {
             "expected alphabetic character",
         ));
     }
+    #[inline]
+    async fn skip_many<S: crate::Source>(src: &mut S) -> Option<usize> {
+        Some(src.skip_while(char::is_alphabetic).await)
+    }
+    fn may_start_with(c: char) -> bool {
+        c.is_alphabetic()
+    }
+}
+
+impl<K> ParseSync<K> for ALPHABETIC {
+    #[inline]
+    fn skip_many_sync<S: crate::SourceSync>(src: &mut S) -> Option<usize> {
+        Some(src.skip_while_sync(char::is_alphabetic))
+    }
+}
+
+impl<K> ParseSend<K> for ALPHABETIC {
+    parse_through_source!();
 }
 
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ALPHANUMERIC;
 
-impl Parse for ALPHANUMERIC {
+impl<K> Parse<K> for ALPHANUMERIC {
     type Output = char;
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if let Some(ch) = src.peek().await {
@@ -275,12 +500,30 @@ impl Parse for ALPHANUMERIC {
             "expected alphanumeric character",
         ));
     }
+    #[inline]
+    async fn skip_many<S: crate::Source>(src: &mut S) -> Option<usize> {
+        Some(src.skip_while(char::is_alphanumeric).await)
+    }
+    fn may_start_with(c: char) -> bool {
+        c.is_alphanumeric()
+    }
+}
+
+impl<K> ParseSync<K> for ALPHANUMERIC {
+    #[inline]
+    fn skip_many_sync<S: crate::SourceSync>(src: &mut S) -> Option<usize> {
+        Some(src.skip_while_sync(char::is_alphanumeric))
+    }
+}
+
+impl<K> ParseSend<K> for ALPHANUMERIC {
+    parse_through_source!();
 }
 
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DIGIT<const RADIX: u8 = 16>;
 
-impl<const RADIX: u8> Parse for DIGIT<RADIX> {
+impl<K, const RADIX: u8> Parse<K> for DIGIT<RADIX> {
     type Output = char;
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if let Some(ch) = src.peek().await {
@@ -295,12 +538,36 @@ impl<const RADIX: u8> Parse for DIGIT<RADIX> {
 
         return Err(Error::new(Span::new(pos, pos), "expected digit"));
     }
+    #[inline]
+    async fn skip_many<S: crate::Source>(src: &mut S) -> Option<usize> {
+        if RADIX == 10 {
+            return Some(src.take_digits().await.len());
+        }
+        return None;
+    }
+    fn may_start_with(c: char) -> bool {
+        c.is_digit(RADIX as _)
+    }
+}
+
+impl<K, const RADIX: u8> ParseSync<K> for DIGIT<RADIX> {
+    #[inline]
+    fn skip_many_sync<S: crate::SourceSync>(src: &mut S) -> Option<usize> {
+        if RADIX == 10 {
+            return Some(src.take_digits_sync().len());
+        }
+        return None;
+    }
+}
+
+impl<K, const RADIX: u8> ParseSend<K> for DIGIT<RADIX> {
+    parse_through_source!();
 }
 
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct CONTROL;
 
-impl Parse for CONTROL {
+impl<K> Parse<K> for CONTROL {
     type Output = char;
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if let Some(ch) = src.peek().await {
@@ -318,6 +585,24 @@ impl Parse for CONTROL {
             "expected control character",
         ));
     }
+    #[inline]
+    async fn skip_many<S: crate::Source>(src: &mut S) -> Option<usize> {
+        Some(src.skip_while(char::is_control).await)
+    }
+    fn may_start_with(c: char) -> bool {
+        c.is_control()
+    }
+}
+
+impl<K> ParseSync<K> for CONTROL {
+    #[inline]
+    fn skip_many_sync<S: crate::SourceSync>(src: &mut S) -> Option<usize> {
+        Some(src.skip_while_sync(char::is_control))
+    }
+}
+
+impl<K> ParseSend<K> for CONTROL {
+    parse_through_source!();
 }
 
 #[cfg(feature = "unicode")]
@@ -325,7 +610,7 @@ impl Parse for CONTROL {
 pub struct UNICODE_ID_START;
 
 #[cfg(feature = "unicode")]
-impl Parse for UNICODE_ID_START {
+impl<K> Parse<K> for UNICODE_ID_START {
     type Output = char;
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if let Some(ch) = src.peek().await {
@@ -342,6 +627,17 @@ impl Parse for UNICODE_ID_START {
             "error parsing unicode_id_start",
         ));
     }
+    fn may_start_with(c: char) -> bool {
+        unicode_ident::is_xid_start(c)
+    }
+}
+
+#[cfg(feature = "unicode")]
+impl<K> ParseSync<K> for UNICODE_ID_START {}
+
+#[cfg(feature = "unicode")]
+impl<K> ParseSend<K> for UNICODE_ID_START {
+    parse_through_source!();
 }
 
 #[cfg(feature = "unicode")]
@@ -349,7 +645,7 @@ impl Parse for UNICODE_ID_START {
 pub struct UNICODE_ID_CONTINUE;
 
 #[cfg(feature = "unicode")]
-impl Parse for UNICODE_ID_CONTINUE {
+impl<K> Parse<K> for UNICODE_ID_CONTINUE {
     type Output = char;
     async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
         if let Some(ch) = src.peek().await {
@@ -366,4 +662,152 @@ impl Parse for UNICODE_ID_CONTINUE {
             "error parsing unicode_id_start",
         ));
     }
+    fn may_start_with(c: char) -> bool {
+        unicode_ident::is_xid_continue(c)
+    }
+}
+
+#[cfg(feature = "unicode")]
+impl<K> ParseSync<K> for UNICODE_ID_CONTINUE {}
+
+#[cfg(feature = "unicode")]
+impl<K> ParseSend<K> for UNICODE_ID_CONTINUE {
+    parse_through_source!();
+}
+
+/// A number of fixed width that can be read from bytes.
+pub trait FixedWidth: Sized + Default {
+    /// the width in bytes
+    const WIDTH: usize;
+    /// `bytes` is `WIDTH` long
+    fn from_be_slice(bytes: &[u8]) -> Self;
+    /// `bytes` is `WIDTH` long
+    fn from_le_slice(bytes: &[u8]) -> Self;
+}
+
+macro_rules! fixed_width {
+    ($($t:ty),*) => {
+        $(
+            impl FixedWidth for $t {
+                const WIDTH: usize = core::mem::size_of::<$t>();
+                fn from_be_slice(bytes: &[u8]) -> Self {
+                    let mut b = [0u8; core::mem::size_of::<$t>()];
+                    b.copy_from_slice(bytes);
+                    <$t>::from_be_bytes(b)
+                }
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    let mut b = [0u8; core::mem::size_of::<$t>()];
+                    b.copy_from_slice(bytes);
+                    <$t>::from_le_bytes(b)
+                }
+            }
+        )*
+    };
+}
+
+fixed_width!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// read the bytes of a `T` into `buf`, which is returned shortened to `T::WIDTH`
+async fn read_fixed<'b, T: FixedWidth, S: crate::Source>(
+    src: &mut S,
+    buf: &'b mut [u8; 16],
+) -> Result<&'b [u8], Error> {
+    let buf = &mut buf[..T::WIDTH];
+    if src.read_bytes(buf).await {
+        return Ok(buf);
+    }
+
+    let pos = src.current_position();
+
+    return Err(Error::new(
+        Span::new(pos, pos),
+        alloc::format!("expected {} bytes", T::WIDTH),
+    ));
+}
+
+/// A big endian number, e.g. `BE<u32>` reads 4 bytes.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct BE<T: FixedWidth>(PhantomData<T>);
+
+impl<K, T: FixedWidth> Parse<K> for BE<T> {
+    type Output = T;
+    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
+        let mut buf = [0u8; 16];
+        let bytes = read_fixed::<T, S>(src, &mut buf).await?;
+        return Ok(T::from_be_slice(bytes));
+    }
+}
+
+impl<K, T: FixedWidth> ParseSync<K> for BE<T> {}
+
+impl<K, T: FixedWidth> ParseSend<K> for BE<T> {
+    parse_through_source!();
+}
+
+/// A little endian number, e.g. `LE<u16>` reads 2 bytes.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct LE<T: FixedWidth>(PhantomData<T>);
+
+impl<K, T: FixedWidth> Parse<K> for LE<T> {
+    type Output = T;
+    async fn parse<S: crate::Source>(src: &mut S) -> Result<Self::Output, Error> {
+        let mut buf = [0u8; 16];
+        let bytes = read_fixed::<T, S>(src, &mut buf).await?;
+        return Ok(T::from_le_slice(bytes));
+    }
+}
+
+impl<K, T: FixedWidth> ParseSync<K> for LE<T> {}
+
+impl<K, T: FixedWidth> ParseSend<K> for LE<T> {
+    parse_through_source!();
+}
+
+/// Parses `T` from the number of bytes given by the length `L`,
+/// e.g. `LengthPrefixed<BE<u32>, Chunk>`.
+///
+/// `T` sees the end of input after the length and must consume all of it.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct LengthPrefixed<L: Parse, T: Parse>(PhantomData<(L, T)>);
+
+combinator! {
+    impl[L, T] LengthPrefixed<L, T>;
+    Parse<Infallible> where [L: Parse, T: Parse, L::Output: TryInto<usize>]
+    ParseSync<Infallible> where [L: ParseSync, T: ParseSync, L::Output: TryInto<usize>]
+    ParseSend<Infallible> where [L: ParseSend, T: ParseSend, L::Output: TryInto<usize>]
+    {
+        type Output = T::Output;
+    }
+    fn parse(src) -> Result<Self::Output, Error> {
+        let start = src.current_position();
+
+        let len: Option<usize> = parse!(L, src)?.try_into().ok();
+
+        let end = match len.and_then(|len| src.current_position().checked_add(len)) {
+            Some(end) => end,
+            None => {
+                let pos = src.current_position();
+                src.set_position(start);
+                return Err(Error::new(Span::new(start, pos), "length out of range"));
+            }
+        };
+
+        let re = parse!(T, &mut crate::source::BoundedSource::new(src, end));
+
+        match re {
+            Ok(v) if src.current_position() == end => return Ok(v),
+            Ok(_) => {
+                let pos = src.current_position();
+                src.set_position(start);
+                return Err(Error::new(
+                    Span::new(pos, end),
+                    "expected end of length-prefixed data",
+                ));
+            }
+            Err(e) => {
+                src.set_position(start);
+                return Err(e);
+            }
+        }
+    }
 }
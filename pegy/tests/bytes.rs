@@ -0,0 +1,96 @@
+use pegy::util::{LengthPrefixed, BE, BYTE, LE};
+use pegy::Span;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar(b"\x89PNG\r\n\x1a\n" $width:BE<u32> $height:BE<u32> $depth:[b'\x01' b'\x02' b'\x04' b'\x08'])]
+struct Header {
+    width: u32,
+    height: u32,
+    depth: u8,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($kind:BYTE $body:LengthPrefixed<LE<u16>, Body>)]
+struct Message {
+    kind: u8,
+    body: Vec<u8>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:[b'a'-b'z']*)]
+struct Body(Vec<u8>);
+
+impl From<Body> for Vec<u8> {
+    fn from(value: Body) -> Self {
+        value.0
+    }
+}
+
+#[test]
+fn test_byte_literals() {
+    let input = b"\x89PNG\r\n\x1a\n\x00\x00\x01\x00\x00\x00\x00\x20\x08";
+    assert_eq!(
+        pegy::parse_blocking::<Header, _>(&input[..]),
+        Ok(Header {
+            width: 256,
+            height: 32,
+            depth: 8
+        })
+    );
+
+    let err = pegy::parse_blocking::<Header, _>(&b"GIF89a"[..]).unwrap_err();
+    assert_eq!(err.span, Span::new(0, 0));
+
+    // bytes outside the class
+    let mut input = input.to_vec();
+    input[16] = 3;
+    assert!(pegy::parse_blocking::<Header, _>(&input[..]).is_err());
+}
+
+#[test]
+fn test_fixed_width() {
+    assert_eq!(
+        pegy::parse_blocking::<BE<u16>, _>(&[0x12, 0x34][..]),
+        Ok(0x1234)
+    );
+    assert_eq!(
+        pegy::parse_blocking::<LE<i32>, _>(&[0xfe, 0xff, 0xff, 0xff][..]),
+        Ok(-2)
+    );
+    assert_eq!(
+        pegy::parse_blocking::<BE<f32>, _>(&1.5f32.to_be_bytes()[..]),
+        Ok(1.5)
+    );
+
+    let err = pegy::parse_blocking::<BE<u32>, _>(&[1, 2][..]).unwrap_err();
+    assert_eq!(err.msg, "expected 4 bytes");
+
+    // str sources are byte addressable as well
+    assert_eq!(pegy::parse_blocking::<LE<u16>, _>("ab"), Ok(0x6261));
+}
+
+#[test]
+fn test_length_prefixed() {
+    assert_eq!(
+        pegy::parse_blocking::<Message, _>(&b"\x07\x03\x00abcdef"[..]),
+        Ok(Message {
+            kind: 7,
+            body: b"abc".to_vec()
+        })
+    );
+
+    // the body must consume the whole length
+    let err = pegy::parse_blocking::<Message, _>(&b"\x07\x03\x00ab!"[..]).unwrap_err();
+    assert_eq!(err.span, Span::new(5, 6));
+
+    // the length runs past the end of input
+    assert!(pegy::parse_blocking::<Message, _>(&b"\x07\x09\x00abc"[..]).is_err());
+}
+
+#[test]
+fn test_length_prefixed_overflow() {
+    // a length that overflows the position is out of range
+    let err = pegy::parse_blocking::<LengthPrefixed<BE<u64>, Body>, _>(&[0xff; 8][..]).unwrap_err();
+    assert_eq!(err.msg, "length out of range");
+    assert_eq!(err.span, Span::new(0, 8));
+}
@@ -0,0 +1,103 @@
+use futures::executor::block_on;
+use futures::io::Cursor;
+use pegy::util::{LengthPrefixed, COMMIT, EOF};
+use pegy::{AsyncStrSource, Parse, Source, Span};
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:u32 '\n' COMMIT)]
+struct Line(u32);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:Line* EOF)]
+struct Lines(Vec<Line>);
+
+#[test]
+fn test_commit_drops_consumed_input() {
+    let text = "abc\n".repeat(1000);
+    let mut src = AsyncStrSource::new(Cursor::new(text.as_bytes()));
+
+    block_on(async {
+        while src.peek().await.is_some() {
+            let span = src.take_until_either('\n', '\n').await;
+            assert_eq!(src.slice(span), Some("abc"));
+            assert!(src.match_char('\n').await);
+
+            src.commit(src.current_position());
+            assert!(src.buffer.len() <= 1024);
+        }
+    });
+
+    // positions stay absolute
+    assert_eq!(src.current_position(), 4000);
+    assert!(src.buffer_start() > 0);
+    assert_eq!(src.slice(Span::new(0, 3)), None);
+}
+
+#[test]
+fn test_commit_keeps_uncommitted_input() {
+    let mut src = AsyncStrSource::new(Cursor::new("abcdef".as_bytes()));
+
+    block_on(async {
+        assert!(src.match_str("abcd").await);
+        // a short prefix is kept until it outgrows the rest
+        src.commit(1);
+        assert_eq!(src.buffer_start(), 0);
+
+        src.commit(4);
+        assert_eq!(src.buffer_start(), 4);
+        assert_eq!(src.slice(Span::new(4, 6)), Some("ef"));
+        assert!(src.starts_with("ef").await);
+        assert_eq!(src.peek_nth(1).await.map(|c| c.ch), Some('f'));
+    });
+}
+
+#[test]
+#[should_panic(expected = "before the committed input")]
+fn test_read_before_commit_panics() {
+    let text = "abc\n".repeat(1000);
+    let mut src = AsyncStrSource::new(Cursor::new(text.as_bytes()));
+
+    block_on(async {
+        assert!(src.match_str(&text[..2000]).await);
+        src.commit(2000);
+        assert!(src.buffer_start() > 0);
+
+        // the dropped input must not read as the end of input
+        src.set_position(0);
+        src.peek().await;
+    });
+}
+
+#[test]
+fn test_commit_in_grammar() {
+    let text = "1\n2\n3\n".repeat(200);
+    let src = AsyncStrSource::new(Cursor::new(text.as_bytes()));
+    let lines = pegy::parse_blocking::<Lines, _>(src).unwrap();
+    assert_eq!(lines.0.len(), 600);
+
+    let src = AsyncStrSource::new(Cursor::new("1\n2\nx".as_bytes()));
+    let err = pegy::parse_blocking::<Lines, _>(src).unwrap_err();
+    assert_eq!(err.span.0, 4);
+}
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:u32 ':')]
+struct Len(u32);
+
+impl From<Len> for usize {
+    fn from(value: Len) -> Self {
+        value.0 as usize
+    }
+}
+
+#[test]
+fn test_commit_in_length_prefixed() {
+    let lines = "1\n2\n3\n".repeat(200);
+    let text = format!("{}:{}", lines.len(), lines);
+    let mut src = AsyncStrSource::new(Cursor::new(text.as_bytes()));
+
+    let lines = block_on(<LengthPrefixed<Len, Lines> as Parse>::parse(&mut src)).unwrap();
+    assert_eq!(lines.0.len(), 600);
+    // the lines commit through the bounded source
+    assert!(src.buffer_start() > 0);
+}
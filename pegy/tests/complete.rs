@@ -0,0 +1,37 @@
+use pegy::util::{RepeatQuiet, ALPHABETIC};
+use pegy::Span;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:u32 ',' $item1:u32)]
+struct Pair(u32, u32);
+
+type Ident = RepeatQuiet<ALPHABETIC, 1>;
+
+#[test]
+fn test_parse_complete() {
+    assert_eq!(
+        pegy::parse_complete_blocking::<Pair, _>("1,2"),
+        Ok(Pair(1, 2))
+    );
+
+    let err = pegy::parse_complete_blocking::<Ident, _>("abc!!!").unwrap_err();
+    assert_eq!(err.span, Span::new(3, 4));
+    assert_eq!(err.msg, "unexpected trailing input");
+
+    // errors of the rule itself are unchanged
+    let err = pegy::parse_complete_blocking::<Pair, _>("1;2").unwrap_err();
+    assert_eq!(err.span.0, 1);
+}
+
+#[test]
+fn test_parse_prefix() {
+    assert_eq!(
+        pegy::parse_prefix_blocking::<Pair, _>("1,23 rest"),
+        Ok((Pair(1, 23), 4))
+    );
+    assert_eq!(
+        pegy::parse_prefix_blocking::<Ident, _>("abc!!!"),
+        Ok(((), 3))
+    );
+    assert_eq!(pegy::parse_blocking::<Ident, _>("abc!!!"), Ok(()));
+}
@@ -0,0 +1,90 @@
+use pegy::util::{RepeatQuiet, EOF, WHITESPACE};
+use pegy::Span;
+
+type __ = RepeatQuiet<WHITESPACE>;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:['a'-'z'] __ '=' __ $item1:u32 __ ';')]
+struct Assign(char, u32);
+
+#[derive(Debug, PartialEq, Eq, pegy::Parse)]
+enum Statement {
+    #[grammar($item0:Assign)]
+    Assign(Assign),
+    #[grammar("return" __ ';')]
+    Return,
+    #[parse(error)]
+    #[grammar(';' | '}')]
+    Error(Span),
+}
+
+impl Default for Statement {
+    fn default() -> Self {
+        Self::Return
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar(__ $item0:Statement __ ';'?)]
+struct Item(Statement);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar('{' $item0:Item* __ '}' __ EOF)]
+struct Block(Vec<Item>);
+
+#[test]
+fn test_error_node() {
+    let (block, errors) =
+        pegy::parse_with_recovery_blocking::<Block, _>("{ a = 1; b = ?; return; }");
+
+    assert_eq!(
+        block,
+        Some(Block(vec![
+            Item(Statement::Assign(Assign('a', 1))),
+            Item(Statement::Error(Span::new(9, 14))),
+            Item(Statement::Return),
+        ]))
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span.0, 13);
+}
+
+#[test]
+fn test_error_node_at_sync_token() {
+    // nothing is skipped in front of the '}', so no error node is produced there
+    let (block, errors) = pegy::parse_with_recovery_blocking::<Block, _>("{ ?? }");
+
+    assert_eq!(
+        block,
+        Some(Block(vec![Item(Statement::Error(Span::new(2, 5)))]))
+    );
+    assert_eq!(errors.len(), 1);
+
+    let (block, errors) = pegy::parse_with_recovery_blocking::<Block, _>("{ }");
+    assert_eq!(block, Some(Block(Vec::new())));
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn test_error_node_requires_recovery() {
+    let err = pegy::parse_blocking::<Block, _>("{ b = ?; }").unwrap_err();
+    assert_eq!(err.span.0, 6);
+}
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:__{2,} __{0,} '.')]
+struct Spaces(Vec<()>);
+
+#[test]
+fn test_empty_repetitions_end() {
+    // whitespace can match nothing, a range repetition stops once it does
+    // and the minimum is met by the empty matches
+    assert_eq!(
+        pegy::parse_blocking::<Spaces, _>("."),
+        Ok(Spaces(vec![(), ()]))
+    );
+    assert_eq!(
+        pegy::parse_blocking::<Spaces, _>("  ."),
+        Ok(Spaces(vec![(), ()]))
+    );
+}
@@ -0,0 +1,67 @@
+use futures::executor::block_on;
+use futures::io::Cursor;
+use futures::StreamExt;
+use pegy::{AsyncStrSource, Parse, Span};
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:u32 '-' $item1:u32 '-' $item2:u32)]
+struct Date(u32, u32, u32);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar("ID-" $item0:['A'-'Z']+ | '#' $item0:['A'-'Z']+)]
+struct Id(Vec<char>);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+enum Token {
+    #[default]
+    #[grammar("true")]
+    True,
+    #[grammar(!"fals" "false"?)]
+    False,
+}
+
+#[test]
+fn test_may_start_with() {
+    assert!(Date::may_start_with('2'));
+    assert!(!Date::may_start_with('a'));
+    assert!(!Date::may_start_with('-'));
+
+    assert!(Id::may_start_with('I'));
+    assert!(Id::may_start_with('#'));
+    assert!(!Id::may_start_with('D'));
+
+    // `False` may match nothing
+    assert!(Token::may_start_with('x'));
+}
+
+#[test]
+fn test_find() {
+    assert_eq!(
+        pegy::find_blocking::<Date, _>("released on 2023-10-05."),
+        Ok(Some((Date(2023, 10, 5), Span::new(12, 22))))
+    );
+    assert_eq!(
+        pegy::find_blocking::<Date, _>("version 1.2-3, 2023-10"),
+        Ok(None)
+    );
+}
+
+#[test]
+fn test_find_iter() {
+    let text = "ID-AB, 12-1-1 and #XY then 2024-1-31";
+
+    let ids = pegy::find_iter_blocking::<Id, _>(text)
+        .map(|re| re.unwrap().1)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![Span::new(0, 5), Span::new(18, 21)]);
+
+    let src = AsyncStrSource::new(Cursor::new(text.as_bytes()));
+    let dates = block_on(pegy::find_iter::<Date, _>(src).collect::<Vec<_>>());
+    assert_eq!(
+        dates,
+        vec![
+            Ok((Date(12, 1, 1), Span::new(7, 13))),
+            Ok((Date(2024, 1, 31), Span::new(27, 36)))
+        ]
+    );
+}
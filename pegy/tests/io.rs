@@ -0,0 +1,66 @@
+use futures::executor::block_on;
+use futures::stream;
+use pegy::io::AsyncStreamRead;
+use pegy::util::EOF;
+use pegy::{AsyncStrSource, ErrorKind, Span};
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:u32 '\n')]
+struct Line(u32);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:Line* EOF)]
+struct Lines(Vec<Line>);
+
+type Chunk = std::io::Result<&'static [u8]>;
+type Chunks = stream::Iter<std::vec::IntoIter<Chunk>>;
+
+fn chunks(
+    chunks: Vec<Chunk>,
+) -> AsyncStrSource<AsyncStreamRead<Chunks, &'static [u8], std::io::Error>> {
+    AsyncStrSource::new(AsyncStreamRead::new(stream::iter(chunks)))
+}
+
+#[test]
+fn test_stream_read_eof() {
+    use futures::AsyncReadExt;
+
+    let mut read = AsyncStreamRead::new(stream::iter(vec![
+        Ok::<_, std::io::Error>(b"ab".as_slice()),
+        Ok(b"".as_slice()),
+        Ok(b"c".as_slice()),
+    ]));
+
+    let mut buf = Vec::new();
+    assert_eq!(block_on(read.read_to_end(&mut buf)).unwrap(), 3);
+    assert_eq!(buf, b"abc");
+    assert_eq!(block_on(read.read(&mut [0; 4])).unwrap(), 0);
+}
+
+#[test]
+fn test_parse_stream_chunks() {
+    let src = chunks(vec![Ok(b"1\n2"), Ok(b""), Ok(b"3\n")]);
+    assert_eq!(
+        pegy::parse_blocking::<Lines, _>(src),
+        Ok(Lines(vec![Line(1), Line(23)]))
+    );
+}
+
+#[test]
+fn test_io_error_is_reported() {
+    let reset = || std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset");
+
+    let src = chunks(vec![Ok(b"1\n2\n"), Err(reset())]);
+    let err = pegy::parse_blocking::<Lines, _>(src).unwrap_err();
+
+    assert_eq!(err.kind, ErrorKind::Io);
+    assert_eq!(err.span, Span::new(4, 4));
+    assert_eq!(err.msg, "failed to read input: connection reset");
+
+    // a cut off input that happens to parse is still an error
+    let src = chunks(vec![Ok(b"1\n"), Err(reset())]);
+    let (value, errors) = pegy::parse_with_recovery_blocking::<Lines, _>(src);
+
+    assert_eq!(value, None);
+    assert!(errors.last().unwrap().is_io());
+}
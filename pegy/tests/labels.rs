@@ -0,0 +1,71 @@
+use pegy::util::WHITESPACE;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[parse(
+    expected = "identifier",
+    code = "E001",
+    help = "identifiers start with a letter"
+)]
+#[grammar($item0:['a'-'z''A'-'Z'] $item1:['a'-'z''A'-'Z''0'-'9']*)]
+struct Ident(char, Vec<char>);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar('{' WHITESPACE* $item0:Ident WHITESPACE* '}' @ "closing brace")]
+struct Block(Ident);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[parse(expected = "statement")]
+enum Statement {
+    #[grammar("let" WHITESPACE+ $item0:Ident)]
+    Let(Ident),
+    #[default]
+    #[grammar("return")]
+    Return,
+}
+
+#[test]
+fn test_rule_label() {
+    let err = pegy::parse_blocking::<Block, _>("{ 9 }").unwrap_err();
+
+    assert_eq!(err.span.0, 2);
+    assert_eq!(err.msg, "expected identifier");
+    assert_eq!(err.expected, vec!["identifier"]);
+    assert_eq!(err.code.as_deref(), Some("E001"));
+    assert_eq!(err.help.as_deref(), Some("identifiers start with a letter"));
+}
+
+#[test]
+fn test_inline_label() {
+    let err = pegy::parse_blocking::<Block, _>("{ abc ;").unwrap_err();
+
+    assert_eq!(err.span.0, 6);
+    assert_eq!(err.msg, "expected closing brace");
+    assert_eq!(err.expected, vec!["closing brace"]);
+    assert_eq!(err.code, None);
+}
+
+#[test]
+fn test_label_keeps_deeper_errors() {
+    // the statement label only applies when no variant made progress
+    let err = pegy::parse_blocking::<Statement, _>("let 1").unwrap_err();
+    assert_eq!(err.span.0, 4);
+    assert_eq!(err.expected, vec!["identifier"]);
+
+    let err = pegy::parse_blocking::<Statement, _>("if").unwrap_err();
+    assert_eq!(err.span.0, 0);
+    assert_eq!(err.msg, "expected statement");
+    assert_eq!(err.expected, vec!["statement"]);
+}
+
+#[test]
+fn test_literal_messages() {
+    #[derive(Debug, Default, pegy::Parse)]
+    #[grammar('a' "bc")]
+    struct Abc;
+
+    let err = pegy::parse_blocking::<Abc, _>("x").unwrap_err();
+    assert_eq!(err.msg, "expected 'a'");
+
+    let err = pegy::parse_blocking::<Abc, _>("ax").unwrap_err();
+    assert_eq!(err.msg, "expected \"bc\"");
+}
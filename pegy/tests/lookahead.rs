@@ -0,0 +1,62 @@
+use pegy::{AsyncStrSource, Error, Parse, Source, StrSource};
+
+/// the characters at 0, 1 and 3 ahead followed by whether `"=>"` comes next,
+/// consuming nothing.
+struct Ahead;
+
+impl Parse for Ahead {
+    type Output = (Option<char>, Option<char>, Option<char>, bool, usize);
+    async fn parse<S: Source>(src: &mut S) -> Result<Self::Output, Error> {
+        let a = src.peek_nth(0).await.map(|c| c.ch);
+        let b = src.peek_nth(1).await.map(|c| c.ch);
+        let d = src.peek_nth(3).await.map(|c| c.ch);
+        let arrow = src.starts_with("=>").await;
+
+        Ok((a, b, d, arrow, src.current_position()))
+    }
+}
+
+#[test]
+fn test_str_source_lookahead() {
+    assert_eq!(
+        pegy::parse_blocking::<Ahead, _>("=>é1"),
+        Ok((Some('='), Some('>'), Some('1'), true, 0))
+    );
+    assert_eq!(
+        pegy::parse_blocking::<Ahead, _>("=é"),
+        Ok((Some('='), Some('é'), None, false, 0))
+    );
+    assert_eq!(
+        pegy::parse_blocking::<Ahead, _>(""),
+        Ok((None, None, None, false, 0))
+    );
+}
+
+#[test]
+fn test_async_source_lookahead() {
+    let src = AsyncStrSource::new(futures::io::Cursor::new("=>é1".as_bytes()));
+    assert_eq!(
+        pegy::parse_blocking::<Ahead, _>(src),
+        Ok((Some('='), Some('>'), Some('1'), true, 0))
+    );
+
+    let src = AsyncStrSource::new(futures::io::Cursor::new("=é".as_bytes()));
+    assert_eq!(
+        pegy::parse_blocking::<Ahead, _>(src),
+        Ok((Some('='), Some('é'), None, false, 0))
+    );
+}
+
+#[test]
+fn test_lookahead_after_position() {
+    let mut src = StrSource::new("let x");
+    src.set_position(4);
+
+    assert_eq!(
+        futures::executor::block_on(src.peek_nth(0)).map(|c| c.ch),
+        Some('x')
+    );
+    assert!(futures::executor::block_on(src.starts_with("x")));
+    assert!(!futures::executor::block_on(src.starts_with("x ")));
+    assert_eq!(src.current_position(), 4);
+}
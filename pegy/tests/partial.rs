@@ -0,0 +1,95 @@
+use pegy::util::ANY;
+use pegy::{ErrorKind, Partial, PartialSource, Span};
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:u32 '\n')]
+struct Line(u32);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar("return" ';')]
+struct Return;
+
+#[test]
+fn test_feed_and_resume() {
+    let mut src = PartialSource::new();
+
+    src.feed(b"12");
+    assert_eq!(
+        pegy::parse_partial_blocking::<Line>(&mut src),
+        Partial::Incomplete { needed_hint: 1 }
+    );
+
+    src.feed(b"3\n4");
+    assert_eq!(
+        pegy::parse_partial_blocking::<Line>(&mut src),
+        Partial::Complete(Line(123))
+    );
+    assert_eq!(
+        pegy::parse_partial_blocking::<Line>(&mut src),
+        Partial::Incomplete { needed_hint: 1 }
+    );
+
+    // the completed line is not parsed again
+    src.feed(b"5\nx\n");
+    assert_eq!(
+        pegy::parse_partial_blocking::<Line>(&mut src),
+        Partial::Complete(Line(45))
+    );
+    assert!(src.buffer_start() > 0);
+
+    match pegy::parse_partial_blocking::<Line>(&mut src) {
+        Partial::Error(e) => assert_eq!(e.span.0, 7),
+        re => panic!("expected an error, got {:?}", re),
+    }
+}
+
+#[test]
+fn test_needed_hint() {
+    let mut src = PartialSource::new();
+
+    src.feed(b"ret");
+    assert_eq!(
+        pegy::parse_partial_blocking::<Return>(&mut src),
+        Partial::Incomplete { needed_hint: 3 }
+    );
+
+    // a split character needs its remaining bytes
+    let mut src = PartialSource::new();
+    src.feed(&"😀".as_bytes()[..1]);
+    assert_eq!(
+        pegy::parse_partial_blocking::<ANY>(&mut src),
+        Partial::Incomplete { needed_hint: 3 }
+    );
+
+    src.feed(&"😀".as_bytes()[1..]);
+    assert_eq!(
+        pegy::parse_partial_blocking::<ANY>(&mut src),
+        Partial::Complete('😀')
+    );
+}
+
+#[test]
+fn test_finish() {
+    let mut src = PartialSource::new();
+
+    src.feed(b"ret");
+    src.finish();
+    assert!(matches!(
+        pegy::parse_partial_blocking::<Return>(&mut src),
+        Partial::Error(_)
+    ));
+
+    let mut src = PartialSource::new();
+    src.feed(b"a\xFF");
+    assert_eq!(
+        pegy::parse_partial_blocking::<ANY>(&mut src),
+        Partial::Complete('a')
+    );
+    match pegy::parse_partial_blocking::<ANY>(&mut src) {
+        Partial::Error(e) => {
+            assert_eq!(e.kind, ErrorKind::InvalidUtf8);
+            assert_eq!(e.span, Span::new(1, 2));
+        }
+        re => panic!("expected an error, got {:?}", re),
+    }
+}
@@ -0,0 +1,80 @@
+use pegy::util::{Recover, RepeatQuiet, EOF, WHITESPACE};
+use pegy::Span;
+
+type __ = RepeatQuiet<WHITESPACE>;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:['a'-'z'] __ '=' __ $item1:u32)]
+struct Assign(char, u32);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar(__ $item0:recover(Assign, until = ';' | '}') __ ';')]
+struct Statement(Assign);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar('{' $item0:Statement* __ '}')]
+struct Block(Vec<Statement>);
+
+#[test]
+fn test_recover_to_sync_token() {
+    let (block, errors) = pegy::parse_with_recovery_blocking::<Block, _>("{ a = 1; b = ; c = 3; }");
+
+    assert_eq!(
+        block,
+        Some(Block(vec![
+            Statement(Assign('a', 1)),
+            Statement(Assign::default()),
+            Statement(Assign('c', 3)),
+        ]))
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span, Span::new(13, 13));
+}
+
+#[test]
+fn test_recover_skips_garbage() {
+    let (block, errors) = pegy::parse_with_recovery_blocking::<Block, _>("{ a = 1; ??? ; x = 2 }");
+
+    // `x = 2` is missing its ';', which is not recovered. The block fails,
+    // so the error recovered inside of it is discarded as well.
+    assert_eq!(block, None);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span.0, 21);
+    assert_eq!(errors[0].expected, vec!["';'"]);
+
+    let (block, errors) = pegy::parse_with_recovery_blocking::<Block, _>("{ a = 1; ??? ; x = 2; }");
+
+    assert_eq!(
+        block,
+        Some(Block(vec![
+            Statement(Assign('a', 1)),
+            Statement(Assign::default()),
+            Statement(Assign('x', 2)),
+        ]))
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span.0, 9);
+}
+
+#[test]
+fn test_backtracked_recovery_is_discarded() {
+    let (block, errors) = pegy::parse_with_recovery_blocking::<Block, _>("{ a = 1; }");
+
+    assert_eq!(block, Some(Block(vec![Statement(Assign('a', 1))])));
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn test_no_recovery_without_recording() {
+    assert!(pegy::parse_blocking::<Block, _>("{ b = ; }").is_err());
+    assert!(pegy::parse_blocking::<Block, _>("{ b = 2; }").is_ok());
+}
+
+#[test]
+fn test_recover_type() {
+    type Item = Recover<Assign, EOF>;
+
+    let (value, errors) = pegy::parse_with_recovery_blocking::<Item, _>("1 = 2");
+    assert_eq!(value, Some(Assign::default()));
+    assert_eq!(errors.len(), 1);
+}
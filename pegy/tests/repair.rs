@@ -0,0 +1,81 @@
+use pegy::util::{RepeatQuiet, EOF, WHITESPACE};
+use pegy::{Report, Span, Suggestion};
+
+type __ = RepeatQuiet<WHITESPACE>;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:['a'-'z'] __ '=' __ $item1:u32)]
+struct Assign(char, u32);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar(__ $item0:Assign __ ';')]
+struct Statement(Assign);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar('{' $item0:Statement* __ '}' __ EOF)]
+struct Block(Vec<Statement>);
+
+#[test]
+fn test_repair_insertion() {
+    let src = "{ a = 1 b = 2; }";
+    let (block, errors) = pegy::parse_with_repair_blocking::<Block>(src, 4);
+
+    assert_eq!(
+        block,
+        Some(Block(vec![
+            Statement(Assign('a', 1)),
+            Statement(Assign('b', 2))
+        ]))
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].msg, "missing ';'");
+    assert_eq!(errors[0].span, Span::new(8, 8));
+    assert_eq!(
+        errors[0].suggestion,
+        Some(Suggestion::new(Span::new(8, 8), ";"))
+    );
+
+    assert!(Report::new(src, &errors[0])
+        .to_string()
+        .ends_with("= suggestion: insert \";\"\n"));
+}
+
+#[test]
+fn test_repair_deletion() {
+    let src = "{ a = 1;, b = 2; }";
+    let (block, errors) = pegy::parse_with_repair_blocking::<Block>(src, 4);
+
+    assert_eq!(
+        block,
+        Some(Block(vec![
+            Statement(Assign('a', 1)),
+            Statement(Assign('b', 2))
+        ]))
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].msg, "unexpected ','");
+    assert_eq!(
+        errors[0].suggestion,
+        Some(Suggestion::new(Span::new(8, 9), ""))
+    );
+}
+
+#[test]
+fn test_repair_multiple() {
+    let (block, errors) = pegy::parse_with_repair_blocking::<Block>("{ a = 1 b = 2 c = 3; }", 4);
+
+    assert_eq!(block.map(|b| b.0.len()), Some(3));
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].span, Span::new(8, 8));
+    assert_eq!(errors[1].span, Span::new(14, 14));
+}
+
+#[test]
+fn test_repair_limit() {
+    let (block, errors) = pegy::parse_with_repair_blocking::<Block>("{ a = 1 b = 2; }", 0);
+
+    assert_eq!(block, None);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span.0, 8);
+    assert_eq!(errors[0].expected, vec!["';'"]);
+}
@@ -0,0 +1,78 @@
+use pegy::util::WHITESPACE;
+use pegy::{Error, Report, Span};
+
+#[derive(Debug, Default, pegy::Parse)]
+#[grammar("let" WHITESPACE+ $item0:['a'-'z'] WHITESPACE* '=' WHITESPACE* $item1:u32 (';' | '}'))]
+struct Let(char, u32);
+
+#[test]
+fn test_report_expected_set() {
+    let src = "let x = 42?";
+    let err = pegy::parse_blocking::<Let, _>(src).unwrap_err();
+
+    assert_eq!(err.span.0, 10);
+    assert_eq!(err.expected, vec!["';'", "'}'"]);
+
+    assert_eq!(
+        Report::new(src, &err).to_string(),
+        concat!(
+            "error: expected ';' or '}'\n",
+            " --> 1:11\n",
+            "  |\n",
+            "1 | let x = 42?\n",
+            "  |           ^ expected ';' or '}'\n",
+        )
+    );
+}
+
+#[test]
+fn test_report_line_column() {
+    let src = "first\nsecond line\nthird";
+    let err = Error::new(Span::new(13, 17), "bad word");
+
+    let report = Report::new(src, &err).with_name("input.txt");
+    assert_eq!(report.location(), (2, 8));
+    assert_eq!(
+        report.to_string(),
+        concat!(
+            "error: bad word\n",
+            " --> input.txt:2:8\n",
+            "  |\n",
+            "2 | second line\n",
+            "  |        ^^^^\n",
+        )
+    );
+}
+
+#[test]
+fn test_report_tabs_and_utf8() {
+    let src = "\tπ = ö?";
+    // span of the '?'
+    let start = src.find('?').unwrap();
+    let err = Error::new(Span::new(start, start + 1), "unexpected '?'");
+
+    let report = Report::new(src, &err);
+    assert_eq!(report.location(), (1, 7));
+    assert_eq!(
+        report.to_string(),
+        concat!(
+            "error: unexpected '?'\n",
+            " --> 1:7\n",
+            "  |\n",
+            "1 |     π = ö?\n",
+            "  |          ^\n",
+        )
+    );
+}
+
+#[test]
+fn test_report_color() {
+    let src = "abc";
+    let err = Error::new(Span::new(1, 2), "oops");
+
+    let plain = Report::new(src, &err).to_string();
+    let colored = Report::new(src, &err).with_color(true).to_string();
+
+    assert!(!plain.contains('\x1b'));
+    assert!(colored.contains("\x1b[1;31merror\x1b[0m"));
+}
@@ -0,0 +1,113 @@
+use pegy::util::{RepeatQuiet, ANY, DIGIT, WHITESPACE};
+use pegy::{Source, Span, StrSource};
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:(_['a'-'z''_'] _['a'-'z''0'-'9''_']*) ';')]
+struct Ident(Span);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:(_['0'-'9']+) ';')]
+struct Number(Span);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar(RepeatQuiet<WHITESPACE> $item0:ANY)]
+struct AfterWhitespace(char);
+
+#[test]
+fn test_take_while() {
+    let mut src = StrSource::new("héllo wörld");
+
+    let span = futures::executor::block_on(src.take_while(|c| c.is_alphabetic()));
+    assert_eq!(span, Span::new(0, 6));
+    assert_eq!(src.current_position(), 6);
+
+    let count = futures::executor::block_on(src.skip_while(|c| c == ' ' || c.is_alphabetic()));
+    assert_eq!(count, 6);
+    assert_eq!(src.current_position(), 13);
+
+    // nothing is consumed when the first character does not match
+    let span = futures::executor::block_on(src.take_while(|c| c.is_alphabetic()));
+    assert_eq!(span, Span::new(13, 13));
+}
+
+#[test]
+fn test_quiet_class_repetition() {
+    assert_eq!(
+        pegy::parse_blocking::<Ident, _>("abc_12;"),
+        Ok(Ident(Span::new(0, 6)))
+    );
+
+    let err = pegy::parse_blocking::<Ident, _>("abc-").unwrap_err();
+    assert_eq!(err.span.0, 3);
+    assert_eq!(err.expected, vec!["'a'..='z'", "'0'..='9'", "'_'", "';'"]);
+}
+
+#[test]
+fn test_quiet_class_repetition_at_least_once() {
+    assert_eq!(
+        pegy::parse_blocking::<Number, _>("123;"),
+        Ok(Number(Span::new(0, 3)))
+    );
+
+    let err = pegy::parse_blocking::<Number, _>(";").unwrap_err();
+    assert_eq!(err.span.0, 0);
+    assert_eq!(err.expected, vec!["'0'..='9'"]);
+}
+
+#[test]
+fn test_skip_whitespace() {
+    // long enough for the word and vector paths, '!' is one bit away from ' '
+    let text = format!("{}\u{3000} \u{b}!", " \t\r\n".repeat(20));
+    let mut src = StrSource::new(&text);
+
+    let count = futures::executor::block_on(src.skip_whitespace());
+    assert_eq!(count, 83);
+    assert_eq!(src.current_position(), text.len() - 1);
+
+    assert_eq!(
+        pegy::parse_blocking::<AfterWhitespace, _>(text.as_str()),
+        Ok(AfterWhitespace('!'))
+    );
+}
+
+#[test]
+fn test_take_digits() {
+    for len in [0, 1, 7, 8, 9, 31, 32, 33, 70] {
+        let text = format!("{}a1", "9".repeat(len));
+        let mut src = StrSource::new(&text);
+
+        let span = futures::executor::block_on(src.take_digits());
+        assert_eq!(span, Span::new(0, len));
+    }
+
+    assert!(pegy::parse_blocking::<RepeatQuiet<DIGIT<10>, 3>, _>("12").is_err());
+    assert!(pegy::parse_blocking::<RepeatQuiet<DIGIT<10>, 3>, _>("123").is_ok());
+}
+
+#[test]
+fn test_take_until_either() {
+    let text = format!("{}\\n\"", "é".repeat(40));
+    let mut src = StrSource::new(&text);
+
+    let span = futures::executor::block_on(src.take_until_either('"', '\\'));
+    assert_eq!(span, Span::new(0, 80));
+
+    src.set_position(81);
+    let span = futures::executor::block_on(src.take_until_either('"', '\\'));
+    assert_eq!(span, Span::new(81, 82));
+
+    src.set_position(83);
+    let span = futures::executor::block_on(src.take_until_either('"', '\\'));
+    assert_eq!(span, Span::new(83, 83));
+}
+
+#[test]
+fn test_integer_digit_runs() {
+    assert_eq!(
+        pegy::parse_blocking::<u64, _>("18446744073709551615"),
+        Ok(u64::MAX)
+    );
+    assert!(pegy::parse_blocking::<u64, _>("18446744073709551616").is_err());
+    assert_eq!(pegy::parse_blocking::<i32, _>("-42"), Ok(-42));
+    assert_eq!(pegy::parse_blocking::<u32, _>("000123x"), Ok(123));
+}
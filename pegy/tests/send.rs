@@ -0,0 +1,81 @@
+use pegy::util::{Recursive, Repeat, RepeatQuiet, LE, WHITESPACE};
+use pegy::{ParseSend, Span};
+
+type __ = RepeatQuiet<WHITESPACE>;
+type Items = Repeat<Recursive<Value>, 0, { usize::MAX }, { ',' as u32 }>;
+
+#[derive(Debug, Default, PartialEq, pegy::Parse, pegy::ParseSend)]
+enum Value {
+    #[default]
+    #[grammar(__ "null" __)]
+    Null,
+    #[grammar(__ $item0:i64 __)]
+    Int(i64),
+    #[grammar(__ $item0:Name __)]
+    Name(Name),
+    #[grammar(__ "[" $item0:Items "]" __)]
+    List(Vec<Value>),
+}
+
+#[derive(Debug, Default, PartialEq, pegy::Parse, pegy::ParseSend)]
+#[grammar($item0:(_ ['a'-'z']+))]
+struct Name(Span);
+
+#[derive(Debug, Default, PartialEq, pegy::Parse, pegy::ParseSend)]
+#[grammar(b"ID" $id:LE<u16>)]
+struct Header {
+    id: u16,
+}
+
+/// spawn a parse of any `Send` rule, the future must be `Send` without knowing `T`
+fn spawn_parse<T: ParseSend + 'static>(
+    rt: &tokio::runtime::Runtime,
+    input: &'static str,
+) -> tokio::task::JoinHandle<pegy::Result<T::Output>>
+where
+    T::Output: Send,
+{
+    rt.spawn(pegy::parse_send::<T, _>(input))
+}
+
+#[test]
+fn test_spawn() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    let value = rt.block_on(spawn_parse::<Value>(&rt, "[1, [null, ab], 2]"));
+    assert_eq!(
+        value.unwrap(),
+        Ok(Value::List(vec![
+            Value::Int(1),
+            Value::List(vec![Value::Null, Value::Name(Name(Span::new(11, 13)))]),
+            Value::Int(2),
+        ]))
+    );
+
+    let header = rt.spawn(pegy::parse_send::<Header, _>(&b"ID\x07\x00"[..]));
+    assert_eq!(rt.block_on(header).unwrap(), Ok(Header { id: 7 }));
+}
+
+#[test]
+fn test_send_matches_async() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    for input in ["[1, 2", "[[x]", "", "1]", "[[[[[[[[7]]]]]]]]"] {
+        let sent = rt.block_on(spawn_parse::<Value>(&rt, input)).unwrap();
+        assert_eq!(sent, pegy::parse_blocking::<Value, _>(input), "{}", input);
+    }
+}
+
+#[test]
+fn test_spawn_async_reader() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    let reader = futures::io::Cursor::new(b"[null, 3]".to_vec());
+    let src = pegy::AsyncStrSource::new(reader);
+    let value = rt.spawn(pegy::parse_send::<Value, _>(src));
+
+    assert_eq!(
+        rt.block_on(value).unwrap(),
+        Ok(Value::List(vec![Value::Null, Value::Int(3)]))
+    );
+}
@@ -0,0 +1,52 @@
+use pegy::util::{RepeatQuiet, ALPHABETIC};
+use pegy::{AsyncStrSource, Error, Parse, Source, Span};
+
+/// a word returned as a `String`, sliced out of the source
+struct Word;
+
+impl Parse for Word {
+    type Output = String;
+    async fn parse<S: Source>(src: &mut S) -> Result<Self::Output, Error> {
+        let start = src.current_position();
+        RepeatQuiet::<ALPHABETIC>::parse(src).await?;
+        let span = Span::new(start, src.current_position());
+
+        match src.slice(span) {
+            Some(s) => Ok(s.to_string()),
+            None => Err(Error::new(span, "source does not retain its input")),
+        }
+    }
+}
+
+#[test]
+fn test_span_helpers() {
+    let a = Span::new(2, 5);
+    let b = Span::new(4, 9);
+
+    assert_eq!(a.len(), 3);
+    assert!(Span::new(3, 3).is_empty());
+    assert_eq!(a.merge(b), Span::new(2, 9));
+    assert!(a.contains(2));
+    assert!(!a.contains(5));
+
+    let range: core::ops::Range<usize> = a.into();
+    assert_eq!(range, 2..5);
+    assert_eq!(&"abcdefg"[a], "cde");
+}
+
+#[test]
+fn test_str_source_slice() {
+    assert_eq!(
+        pegy::parse_blocking::<Word, _>("héllo world"),
+        Ok("héllo".to_string())
+    );
+}
+
+#[test]
+fn test_async_source_slice() {
+    let src = AsyncStrSource::new(futures::io::Cursor::new("héllo world".as_bytes()));
+    assert_eq!(
+        pegy::parse_blocking::<Word, _>(src),
+        Ok("héllo".to_string())
+    );
+}
@@ -0,0 +1,98 @@
+use pegy::util::{RepeatQuiet, EOF, WHITESPACE};
+use pegy::{FileSpan, LineCol, LineIndex, SourceMap, Span};
+
+type __ = RepeatQuiet<WHITESPACE>;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar(__ $item0:['a'-'z'] __ '=' __ $item1:u32 __ ';')]
+struct Assign(char, u32);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:Assign* __ EOF)]
+struct Config(Vec<Assign>);
+
+#[test]
+fn test_line_index() {
+    let text = "ab\ncd\n\nef";
+    let index = LineIndex::new(text);
+
+    assert_eq!(index.line_count(), 4);
+    assert_eq!(index.line_col(0), LineCol { line: 0, col: 0 });
+    assert_eq!(index.line_col(2), LineCol { line: 0, col: 2 });
+    assert_eq!(index.line_col(3), LineCol { line: 1, col: 0 });
+    assert_eq!(index.line_col(6), LineCol { line: 2, col: 0 });
+    assert_eq!(index.line_col(8), LineCol { line: 3, col: 1 });
+    assert_eq!(index.offset(LineCol { line: 3, col: 1 }), Some(8));
+}
+
+#[test]
+fn test_utf16_columns() {
+    // 'é' is 2 bytes and 1 code unit, '𝄞' is 4 bytes and 2 code units
+    let text = "x\né𝄞y";
+    let index = LineIndex::new(text);
+    let offset = text.find('y').unwrap();
+
+    assert_eq!(index.line_col(offset), LineCol { line: 1, col: 6 });
+    assert_eq!(
+        index.line_col_utf16(text, offset),
+        LineCol { line: 1, col: 3 }
+    );
+    assert_eq!(
+        index.line_col_chars(text, offset),
+        LineCol { line: 1, col: 2 }
+    );
+}
+
+#[test]
+fn test_errors_know_their_file() {
+    let mut map = SourceMap::new();
+    let main = map.add("main.cfg", "a = 1;\n");
+    let included = map.add("file.cfg", "a = 1;\nb = 2;\n\n\n\n\n\n\n\n\n\nc = ;\n");
+
+    assert!(pegy::parse_blocking::<Config, _>(map.source(main)).is_ok());
+
+    let err = pegy::parse_blocking::<Config, _>(map.source(included)).unwrap_err();
+    let span = err.file_span().unwrap();
+
+    assert_eq!(
+        span,
+        FileSpan::new(included, Span::new(err.span.0, err.span.1))
+    );
+    assert_eq!(map.location(span).unwrap().to_string(), "file.cfg:12:5");
+    assert!(map
+        .report(&err)
+        .unwrap()
+        .to_string()
+        .contains(" --> file.cfg:12:5\n"));
+}
+
+#[test]
+fn test_recovered_errors_know_their_file() {
+    let mut map = SourceMap::new();
+    let file = map.add("a.cfg", "a = ;");
+
+    let (_, errors) = pegy::parse_with_recovery_blocking::<Config, _>(map.source(file));
+    assert!(errors.iter().all(|e| e.file == Some(file)));
+
+    // errors of an unbound source have no file
+    let err = pegy::parse_blocking::<Config, _>("a = ;").unwrap_err();
+    assert_eq!(err.file, None);
+}
+
+#[test]
+fn test_location_matches_report() {
+    let text = "a = 1;\né = ;\n";
+    let mut map = SourceMap::new();
+    let file = map.add("unicode.cfg", text);
+
+    // 'é' is 2 bytes but a single column
+    let offset = text.rfind(';').unwrap();
+    let err = pegy::Error::new(Span::new(offset, offset), "expected u32");
+    let location = map.location(FileSpan::new(file, err.span)).unwrap();
+
+    assert_eq!(location.to_string(), "unicode.cfg:2:5");
+    assert_eq!(
+        pegy::Report::new(text, &err).location(),
+        (location.line, location.column)
+    );
+}
@@ -0,0 +1,63 @@
+use futures::executor::block_on;
+use futures::io::Cursor;
+use futures::StreamExt;
+use pegy::util::{RepeatQuiet, WHITESPACE};
+use pegy::AsyncStrSource;
+
+type __ = RepeatQuiet<WHITESPACE>;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:u32 ',' $item1:u32)]
+struct Record(u32, u32);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar('\n')]
+struct Newline;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:u32 __)]
+struct Number(u32);
+
+#[test]
+fn test_parse_stream_separated() {
+    let text = "1,2\n".repeat(500);
+    let src = AsyncStrSource::new(Cursor::new(text.as_bytes()));
+
+    let records =
+        block_on(pegy::parse_stream_separated::<Record, Newline, _>(src).collect::<Vec<_>>());
+
+    assert_eq!(records.len(), 500);
+    assert!(records.iter().all(|r| r == &Ok(Record(1, 2))));
+
+    // without a trailing separator
+    let records = block_on(
+        pegy::parse_stream_separated::<Record, Newline, _>("1,2\n3,4").collect::<Vec<_>>(),
+    );
+    assert_eq!(records, vec![Ok(Record(1, 2)), Ok(Record(3, 4))]);
+}
+
+#[test]
+fn test_parse_stream_stops_at_error() {
+    let records = block_on(
+        pegy::parse_stream_separated::<Record, Newline, _>("1,2\nx\n3,4\n").collect::<Vec<_>>(),
+    );
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0], Ok(Record(1, 2)));
+    assert_eq!(records[1].as_ref().unwrap_err().span.0, 4);
+
+    // a missing separator is an error
+    let records =
+        block_on(pegy::parse_stream_separated::<Record, Newline, _>("1,2 3,4").collect::<Vec<_>>());
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].as_ref().unwrap_err().span.0, 3);
+}
+
+#[test]
+fn test_parse_stream() {
+    let numbers = block_on(pegy::parse_stream::<Number, _>("1 2\n3 ").collect::<Vec<_>>());
+    assert_eq!(numbers, vec![Ok(Number(1)), Ok(Number(2)), Ok(Number(3))]);
+
+    let numbers = block_on(pegy::parse_stream::<Number, _>("").collect::<Vec<_>>());
+    assert!(numbers.is_empty());
+}
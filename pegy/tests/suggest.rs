@@ -0,0 +1,64 @@
+use pegy::util::{RepeatQuiet, WHITESPACE};
+use pegy::{Report, Span, Suggestion};
+
+type __ = RepeatQuiet<WHITESPACE>;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+enum Keyword {
+    #[default]
+    #[grammar("function")]
+    Function,
+    #[grammar("return")]
+    Return,
+    #[grammar("let")]
+    Let,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar(__ $item0:Keyword)]
+struct Item(Keyword);
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:Item* __ ';')]
+struct Items(Vec<Item>);
+
+#[test]
+fn test_did_you_mean() {
+    let src = "fucntion";
+    let err = pegy::parse_blocking::<Keyword, _>(src).unwrap_err();
+
+    assert_eq!(
+        err.suggestion,
+        Some(Suggestion::new(Span::new(0, 8), "function"))
+    );
+    assert!(Report::new(src, &err)
+        .to_string()
+        .ends_with("= suggestion: did you mean \"function\"?\n"));
+
+    let err = pegy::parse_blocking::<Keyword, _>("retrn").unwrap_err();
+    assert_eq!(
+        err.suggestion,
+        Some(Suggestion::new(Span::new(0, 5), "return"))
+    );
+}
+
+#[test]
+fn test_no_suggestion_for_distant_words() {
+    let err = pegy::parse_blocking::<Keyword, _>("while").unwrap_err();
+    assert_eq!(err.suggestion, None);
+
+    let err = pegy::parse_blocking::<Keyword, _>("(").unwrap_err();
+    assert_eq!(err.suggestion, None);
+}
+
+#[test]
+fn test_did_you_mean_with_recovery() {
+    let (value, errors) = pegy::parse_with_recovery_blocking::<Items, _>("let retrun;");
+
+    assert_eq!(value, None);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].suggestion,
+        Some(Suggestion::new(Span::new(4, 10), "return"))
+    );
+}
@@ -0,0 +1,70 @@
+use pegy::util::{Boxed, Recursive, ALPHABETIC, DIGIT, WHITESPACE};
+use pegy::{ParseSync, Source, Span, StrSource};
+
+type Value = Recursive<Boxed<Expr>>;
+
+#[derive(Debug, Default, PartialEq, pegy::Parse, pegy::ParseSync)]
+enum Expr {
+    #[default]
+    #[grammar("nil")]
+    Nil,
+    #[grammar('(' $item0:Value ')')]
+    Paren(Box<Expr>),
+    #[grammar($item0:f64 "f")]
+    Float(f64),
+    #[grammar($item0:i64)]
+    Int(i64),
+    #[grammar($item0:Ident)]
+    Name(Ident),
+}
+
+#[derive(Debug, Default, PartialEq, pegy::Parse, pegy::ParseSync)]
+#[grammar($item0:(_ ALPHABETIC+))]
+struct Ident(Span);
+
+#[test]
+fn test_parse_sync() {
+    assert_eq!(
+        pegy::parse_sync::<Expr, _>("((42))"),
+        Ok(Expr::Paren(Box::new(Expr::Paren(Box::new(Expr::Int(42))))))
+    );
+    assert_eq!(pegy::parse_sync::<Expr, _>("2.5f"), Ok(Expr::Float(2.5)));
+    assert_eq!(
+        pegy::parse_sync::<Expr, _>("abc"),
+        Ok(Expr::Name(Ident(Span::new(0, 3))))
+    );
+}
+
+#[test]
+fn test_sync_matches_async() {
+    for input in ["(1", "((x)", "", "1)", "((((((((7))))))))"] {
+        assert_eq!(
+            pegy::parse_sync::<Expr, _>(input),
+            pegy::parse_blocking::<Expr, _>(input),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_deep_nesting() {
+    // no future is pinned per level
+    let input = format!("{}1{}", "(".repeat(2000), ")".repeat(2000));
+    let handle = std::thread::Builder::new()
+        .stack_size(64 << 20)
+        .spawn(move || pegy::parse_sync::<Expr, _>(StrSource::new(&input)).is_ok())
+        .unwrap();
+    assert!(handle.join().unwrap());
+}
+
+#[test]
+fn test_skip_many_sync() {
+    let mut src = StrSource::new("  \tabc1");
+    assert_eq!(<WHITESPACE as ParseSync>::skip_many_sync(&mut src), Some(3));
+    assert_eq!(<ALPHABETIC as ParseSync>::skip_many_sync(&mut src), Some(3));
+    assert_eq!(src.current_position(), 6);
+    // only decimal digits skip in bulk
+    assert_eq!(<DIGIT<16> as ParseSync>::skip_many_sync(&mut src), None);
+    assert_eq!(<DIGIT<10> as ParseSync>::skip_many_sync(&mut src), Some(1));
+}
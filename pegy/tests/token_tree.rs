@@ -0,0 +1,73 @@
+#![cfg(feature = "proc-macro2")]
+
+use pegy::token_tree::{Braced, Ident, Literal, Punct};
+use proc_macro2::{TokenStream, TokenTree};
+
+#[derive(Debug, Default, pegy::Parse)]
+#[grammar("struct" $name:Ident $fields:Braced<Fields>)]
+#[parse(token = TokenTree)]
+struct Item {
+    name: Option<proc_macro2::Ident>,
+    fields: Fields,
+}
+
+#[derive(Debug, Default, pegy::Parse)]
+#[grammar($item0:Field*)]
+#[parse(token = TokenTree)]
+struct Fields(Vec<Field>);
+
+#[derive(Debug, Default, pegy::Parse)]
+#[grammar($name:Ident ':' $ty:Ident ("=>" $default:Literal)? Punct<','>)]
+#[parse(token = TokenTree)]
+struct Field {
+    name: Option<proc_macro2::Ident>,
+    ty: Option<proc_macro2::Ident>,
+    default: Option<proc_macro2::Literal>,
+}
+
+fn tokens(s: &str) -> TokenStream {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_token_tree_terms() {
+    let item =
+        pegy::token_tree::parse_blocking::<Item>(tokens("struct Point { x: i32 => 1, y: i32, }"))
+            .unwrap();
+
+    assert_eq!(item.name.unwrap(), "Point");
+    assert_eq!(item.fields.0.len(), 2);
+    assert_eq!(item.fields.0[1].name.as_ref().unwrap(), "y");
+    assert_eq!(item.fields.0[0].ty.as_ref().unwrap(), "i32");
+    assert_eq!(item.fields.0[0].default.as_ref().unwrap().to_string(), "1");
+    assert!(item.fields.0[1].default.is_none());
+}
+
+#[test]
+fn test_punct_runs_are_joint() {
+    let err =
+        pegy::token_tree::parse_blocking::<Item>(tokens("struct A { x: i32 = > 1, }")).unwrap_err();
+    assert_eq!(err.to_string(), "unexpected token");
+}
+
+#[test]
+fn test_group_content_must_match() {
+    // `Fields` stops at `+`, the group is not closed there
+    let err =
+        pegy::token_tree::parse_blocking::<Item>(tokens("struct A { x: u8, + }")).unwrap_err();
+    assert_eq!(err.to_string(), "unexpected token");
+
+    let err =
+        pegy::token_tree::parse_blocking::<Item>(tokens("struct A { } trailing")).unwrap_err();
+    assert_eq!(err.to_string(), "unexpected trailing tokens");
+}
+
+#[test]
+fn test_syn_error_span() {
+    let input = tokens("struct\n  123 {}");
+    let err = pegy::token_tree::parse_blocking::<Item>(input).unwrap_err();
+
+    assert_eq!(err.to_string(), "expected identifier");
+    assert_eq!(err.span().start().line, 2);
+    assert_eq!(err.span().start().column, 2);
+}
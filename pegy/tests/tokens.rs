@@ -0,0 +1,221 @@
+use pegy::util::EOF;
+use pegy::{Error, MatchToken, Parse, Source, Span};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+enum Token {
+    #[default]
+    Unknown,
+    Ident(String, Span),
+    Num(i64, Span),
+    Eq(Span),
+    Semi(Span),
+}
+
+impl pegy::Token for Token {
+    fn span(&self) -> Span {
+        match self {
+            Token::Ident(_, s) | Token::Num(_, s) | Token::Eq(s) | Token::Semi(s) => *s,
+            Token::Unknown => Span::new(0, 0),
+        }
+    }
+}
+
+/// a hand-written lexer over `name = 12;` statements
+fn lex(s: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let mut end = i + c.len_utf8();
+        let mut take = |f: fn(char) -> bool| {
+            while let Some((j, c)) = chars.next_if(|(_, c)| f(*c)) {
+                end = j + c.len_utf8();
+            }
+            end
+        };
+        match c {
+            '=' => tokens.push(Token::Eq(Span::new(i, end))),
+            ';' => tokens.push(Token::Semi(Span::new(i, end))),
+            c if c.is_alphabetic() => {
+                let end = take(char::is_alphanumeric);
+                tokens.push(Token::Ident(s[i..end].to_string(), Span::new(i, end)));
+            }
+            c if c.is_ascii_digit() => {
+                let end = take(|c| c.is_ascii_digit());
+                tokens.push(Token::Num(s[i..end].parse().unwrap(), Span::new(i, end)));
+            }
+            _ => {}
+        }
+    }
+    return tokens;
+}
+
+#[derive(Debug, Default, PartialEq, pegy::Parse)]
+#[grammar($name:token(Token::Ident(..)) token(Token::Eq(_)) $value:token(Token::Num(..)) token(Token::Semi(_)))]
+struct Assign {
+    name: Token,
+    value: Token,
+}
+
+#[derive(Debug, Default, PartialEq, pegy::Parse)]
+#[grammar($item0:Assign* EOF)]
+#[parse(token = Token)]
+struct Program(Vec<Assign>);
+
+#[test]
+fn test_token_patterns() {
+    let tokens = lex("a = 1;\nbc = 23;");
+    let program = pegy::parse_blocking::<Program, _>(&tokens[..]).unwrap();
+
+    assert_eq!(program.0.len(), 2);
+    assert_eq!(
+        program.0[1].name,
+        Token::Ident("bc".into(), Span::new(7, 9))
+    );
+    assert_eq!(program.0[1].value, Token::Num(23, Span::new(12, 14)));
+}
+
+#[test]
+fn test_token_spans() {
+    // errors point into the text, not at token indices
+    let text = "a = 1;\nb = ;";
+    let tokens = lex(text);
+    let err = pegy::parse_blocking::<Program, _>(&tokens[..]).unwrap_err();
+
+    assert_eq!(err.span, Span::new(11, 11));
+    assert!(err.expected.iter().any(|e| e == "Token::Num(..)"));
+}
+
+#[test]
+fn test_token_eof() {
+    let tokens = lex("a = 1; 2");
+    assert!(pegy::parse_blocking::<Program, _>(&tokens[..]).is_err());
+
+    let (_, end) = pegy::parse_prefix_blocking::<Assign, _>(&tokens[..]).unwrap();
+    assert_eq!(end, 7);
+
+    assert_eq!(
+        pegy::parse_blocking::<EOF, _>(&Vec::<Token>::new()[..]),
+        Ok(())
+    );
+}
+
+/// a token borrowing its text from the input
+#[derive(Debug, Default, Clone, PartialEq)]
+enum Word<'src> {
+    #[default]
+    Unknown,
+    Name(&'src str, Span),
+    Comma(Span),
+}
+
+impl<'src> pegy::Token for Word<'src> {
+    fn span(&self) -> Span {
+        match self {
+            Word::Name(_, s) | Word::Comma(s) => *s,
+            Word::Unknown => Span::new(0, 0),
+        }
+    }
+}
+
+fn lex_words(s: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut pos = 0;
+    for part in s.split_inclusive(',') {
+        let name = part.trim_end_matches(',');
+        words.push(Word::Name(name, Span::new(pos, pos + name.len())));
+        if name.len() < part.len() {
+            words.push(Word::Comma(Span::new(pos + name.len(), pos + part.len())));
+        }
+        pos += part.len();
+    }
+    return words;
+}
+
+#[derive(Debug, Default, PartialEq, pegy::Parse, pegy::ParseSend)]
+#[grammar($first:token(Word::Name(..)) (token(Word::Comma(_)) $second:token(Word::Name(..)))?)]
+#[parse(token = Word<'src>)]
+struct Pair<'src> {
+    first: Word<'src>,
+    second: Word<'src>,
+}
+
+#[test]
+fn test_borrowed_tokens() {
+    let text = String::from("ab,cd");
+    let words = lex_words(&text);
+    let pair = pegy::parse_blocking::<Pair, _>(&words[..]).unwrap();
+
+    assert_eq!(pair.first, Word::Name("ab", Span::new(0, 2)));
+    assert_eq!(pair.second, Word::Name("cd", Span::new(3, 5)));
+
+    let sent = futures::executor::block_on(pegy::parse_send::<Pair, _>(&words[..]));
+    assert_eq!(sent, Ok(pair));
+}
+
+/// rules given by a path are rules, not token patterns
+#[derive(Debug, Default, PartialEq, pegy::Parse)]
+#[grammar($item0:self::Assign pegy::util::EOF)]
+#[parse(token = Token)]
+struct Single(Assign);
+
+#[derive(Debug, Default, PartialEq, pegy::Parse)]
+#[grammar($item0:pegy::util::DIGIT<10> ',' $item1:core::primitive::u32)]
+struct Digits(char, u32);
+
+#[test]
+fn test_path_rules() {
+    let single = pegy::parse_blocking::<Single, _>(&lex("a = 1;")[..]).unwrap();
+    assert_eq!(single.0.value, Token::Num(1, Span::new(4, 5)));
+    assert!(pegy::parse_blocking::<Single, _>(&lex("a = 1; b")[..]).is_err());
+
+    assert_eq!(pegy::parse_blocking::<Digits, _>("1,23"), Ok(Digits('1', 23)));
+}
+
+/// a rule over text written against `Source`
+struct Number;
+
+impl Parse for Number {
+    type Output = u32;
+    async fn parse<S: Source>(src: &mut S) -> Result<Self::Output, Error> {
+        <u32 as Parse>::parse(src).await
+    }
+}
+
+/// a hand-written rule over tokens
+struct Semi;
+
+impl Parse<Token> for Semi {
+    type Output = Token;
+    async fn parse<S: MatchToken<Token>>(src: &mut S) -> Result<Self::Output, Error> {
+        match src.match_token(|t| matches!(t, Token::Semi(_))) {
+            Some(t) => Ok(t),
+            None => {
+                let pos = src.current_position();
+                Err(Error::new(Span::new(pos, pos), "expected ';'"))
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hand_written_rules() {
+    assert_eq!(pegy::parse_blocking::<Number, _>("12"), Ok(12));
+
+    let tokens = lex(";");
+    assert_eq!(
+        pegy::parse_blocking::<Semi, _>(&tokens[..]),
+        Ok(Token::Semi(Span::new(0, 1)))
+    );
+    assert!(pegy::parse_blocking::<Semi, _>(&lex("a")[..]).is_err());
+}
+
+#[test]
+fn test_token_warnings() {
+    let tokens = lex("a = 1;");
+    let (program, warnings) =
+        pegy::parse_with_warnings_blocking::<Program, _>(&tokens[..]).unwrap();
+
+    assert_eq!(program.0.len(), 1);
+    assert!(warnings.is_empty());
+}
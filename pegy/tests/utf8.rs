@@ -0,0 +1,44 @@
+use futures::io::Cursor;
+use pegy::util::{ANY, EOF};
+use pegy::{AsyncStrSource, ErrorKind, Span, Utf8Policy};
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:ANY* EOF)]
+struct Chars(Vec<char>);
+
+fn source(bytes: &'static [u8], policy: Utf8Policy) -> AsyncStrSource<Cursor<&'static [u8]>> {
+    AsyncStrSource::new(Cursor::new(bytes)).with_utf8_policy(policy)
+}
+
+#[test]
+fn test_strict_reports_offset() {
+    let err =
+        pegy::parse_blocking::<Chars, _>(source(b"ab\xE2\x82x", Utf8Policy::Strict)).unwrap_err();
+
+    assert_eq!(err.kind, ErrorKind::InvalidUtf8);
+    assert_eq!(err.span, Span::new(2, 4));
+    assert_eq!(err.msg, "invalid utf-8 sequence at byte 2");
+
+    // valid multibyte characters are not affected
+    assert_eq!(
+        pegy::parse_blocking::<Chars, _>(source("é€😀".as_bytes(), Utf8Policy::Strict)),
+        Ok(Chars(vec!['é', '€', '😀']))
+    );
+}
+
+#[test]
+fn test_lossy_replaces_invalid_sequences() {
+    let bytes: &[u8] = b"a\xF0\x9F\x98b\xFFc\xED\xA0\x80";
+    assert_eq!(
+        pegy::parse_blocking::<Chars, _>(source(bytes, Utf8Policy::Lossy)),
+        Ok(Chars(String::from_utf8_lossy(bytes).chars().collect()))
+    );
+}
+
+#[test]
+fn test_bytes_keeps_every_byte() {
+    assert_eq!(
+        pegy::parse_blocking::<Chars, _>(source(b"a\xE9\xFFb", Utf8Policy::Bytes)),
+        Ok(Chars(vec!['a', '\u{E9}', '\u{FF}', 'b']))
+    );
+}
@@ -0,0 +1,74 @@
+use pegy::util::{RepeatQuiet, WHITESPACE};
+use pegy::{Report, Severity, Span};
+
+type __ = RepeatQuiet<WHITESPACE>;
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+enum Keyword {
+    #[default]
+    #[grammar("fn")]
+    Fn,
+    #[parse(warn = "`function` is deprecated, use `fn`")]
+    #[grammar("function")]
+    Function,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, pegy::Parse)]
+#[grammar($item0:Keyword __ $item1:['a'-'z'] __ ';')]
+struct Decl(Keyword, char);
+
+/// matches `function x` with the deprecated keyword first,
+/// then backtracks to a declaration.
+#[derive(Debug, PartialEq, Eq, pegy::Parse)]
+enum Item {
+    #[grammar($item0:Decl __ '!')]
+    Exported(Decl),
+    #[grammar($item0:Decl)]
+    Private(Decl),
+}
+
+impl Default for Item {
+    fn default() -> Self {
+        Self::Private(Decl::default())
+    }
+}
+
+#[test]
+fn test_warning_returned_with_value() {
+    let src = "function f;";
+    let (value, warnings) = pegy::parse_with_warnings_blocking::<Decl, _>(src).unwrap();
+
+    assert_eq!(value, Decl(Keyword::Function, 'f'));
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert_eq!(warnings[0].span, Span::new(0, 8));
+    assert!(Report::new(src, &warnings[0])
+        .to_string()
+        .starts_with("warning: `function` is deprecated, use `fn`\n"));
+
+    let (_, warnings) = pegy::parse_with_warnings_blocking::<Decl, _>("fn f;").unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_backtracked_warnings_are_discarded() {
+    // the first variant emits the warning and then fails
+    let (value, warnings) = pegy::parse_with_warnings_blocking::<Item, _>("function f;").unwrap();
+
+    assert_eq!(value, Item::Private(Decl(Keyword::Function, 'f')));
+    assert_eq!(warnings.len(), 1);
+
+    let (value, warnings) = pegy::parse_with_warnings_blocking::<Item, _>("function f; !").unwrap();
+
+    assert_eq!(value, Item::Exported(Decl(Keyword::Function, 'f')));
+    assert_eq!(warnings.len(), 1);
+}
+
+#[test]
+fn test_warnings_without_collector() {
+    assert_eq!(
+        pegy::parse_blocking::<Decl, _>("function f;"),
+        Ok(Decl(Keyword::Function, 'f'))
+    );
+    assert!(pegy::parse_with_warnings_blocking::<Decl, _>("function f").is_err());
+}